@@ -1,108 +1,290 @@
 #![allow(dead_code)]
 
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use toml_edit::{DocumentMut, ImDocument, Item, Key, Table, TableLike, Value};
 
-/// Holds config, value is either a `String` or a nested `ConfigMap`
+/// Holds config, value is either a `String`, a nested `ConfigMap` or an array
 pub type ConfigMap = HashMap<String, ConfigElement>;
 
-/// Element of Config, either a `String` for a setting
-/// or a nested `ConfigMap`
+/// Element of Config, either a `String` for a setting,
+/// a scalar (numbers, booleans and dates as written), a
+/// nested `ConfigMap` (tables and inline tables) or an
+/// array (arrays and arrays of tables), each with the
+/// location of its key in the file
+/// environment variables and overrides are not typed and
+/// always settings
 #[derive(Clone, Debug)]
 pub enum ConfigElement {
-    Setting(String),
-    Nested(ConfigMap),
+    Setting(String, Location),
+    Scalar(String, Location),
+    Nested(ConfigMap, Location),
+    Array(Vec<ConfigElement>, Location),
 }
 
-/// parse toml'ish file into a structure (`ConfigMap`)
-/// as an intermediate step to parsing the configuration
-pub fn parse_config_file(input: &String) -> (ConfigMap, Vec<String>) {
-    let mut config = ConfigMap::new();
-    let mut pointer: &mut ConfigMap = &mut config;
-    let mut messages: Vec<String> = Vec::<String>::new();
-    let lines = input.lines();
+impl ConfigElement {
+    /// returns where the element is defined in the file
+    pub fn location(&self) -> Location {
+        match self {
+            ConfigElement::Setting(_, location) => *location,
+            ConfigElement::Scalar(_, location) => *location,
+            ConfigElement::Nested(_, location) => *location,
+            ConfigElement::Array(_, location) => *location,
+        }
+    }
+}
 
-    for (n, line) in lines.enumerate() {
-        let line = line.trim();
+/// position in the configuration file, line and column start at 1
+/// (0 if the position is unknown)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
 
-        // ignore empty lines
-        if line.is_empty() {
-            continue;
-        }
+impl Location {
+    /// converts the byte offset `offset` in `input` to line and column
+    pub fn from_offset(input: &str, offset: usize) -> Self {
+        let offset = offset.min(input.len());
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |n| n + 1);
+        let column = before[line_start..].chars().count() + 1;
+        Location { line, column }
+    }
 
-        // ignore comments
-        if line.starts_with("#") {
-            continue;
-        }
+    fn from_span(input: &str, span: Option<Range<usize>>) -> Self {
+        span.map_or_else(Location::default, |span| Location::from_offset(input, span.start))
+    }
+}
 
-        // remove inline comments
-        let line: String = remove_inline_comment(line);
-
-        if line.starts_with("[[") {
-            messages.push(format!("error on line #{n} - arrays are not supported in config file:\n{}", line));
-        } else if line.starts_with("[") {
-            // check for table
-            if !line.ends_with("]") {
-                messages.push(format!("error on line #{n} - table name is not properly terminated (missing ']'):\n{}", line));
-            } else if line.contains([' ', '\t']) {
-                messages.push(format!("error on line #{n} - no white space allowed in table names"));
-            }
-            let mut tokens: Vec<&str> = line.split(['.', '[', ']']).map(|entry| entry.trim()).collect();
-            tokens.retain(|entry| !entry.is_empty());
-
-            pointer = &mut config;
-            for token in tokens {
-                pointer = match pointer.entry(token.to_string()).or_insert(ConfigElement::Nested(ConfigMap::new())) {
-                    ConfigElement::Nested(entry) => entry,
-                    _ => {
-                        panic!("error occured handling line #{} - tried to insert a `Nested` element into ConfigMap, but found a `Setting` element of the same name ({})", n, token);
-                    }
-                };
-            }
+/// message about the configuration file, e.g. a syntax error or an unknown setting
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub location: Location,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(location: Location, message: String) -> Self {
+        Self { location, message }
+    }
+}
+
+/// formats as `<line>:<column>: <message>`, prefix it with the file name
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.location == Location::default() {
+            write!(f, "{}", self.message)
         } else {
-            // check for config
-            let mut tokens: Vec<&str> = line.splitn(2, '=').map(|entry| entry.trim()).collect();
-            tokens.retain(|entry| !entry.is_empty());
-            // check for valid input
-            if tokens.len() != 2 {
-                // println!("error in line'", line);
-                continue;
-            }
-            // remove characters not wanted in output
-            let key = tokens[0];
-            let value = tokens[1].replace(&['\"', '\''][..], "");
+            write!(f, "{}:{}: {}", self.location.line, self.location.column, self.message)
+        }
+    }
+}
 
-            pointer.insert(key.to_string(), ConfigElement::Setting(value));
+/// parse toml file into a structure (`ConfigMap`)
+/// as an intermediate step to parsing the configuration
+/// strings are unescaped, other values are kept as written (e.g. `0x10`)
+pub fn parse_config_file(input: &str) -> (ConfigMap, Vec<Diagnostic>) {
+    let mut messages: Vec<Diagnostic> = Vec::<Diagnostic>::new();
+    let document = match ImDocument::parse(input) {
+        Ok(value) => value,
+        Err(error) => {
+            let location = Location::from_span(input, error.span());
+            let message = error.message().trim_end().lines().collect::<Vec<&str>>().join(": ");
+            messages.push(Diagnostic::new(location, message));
+            return (ConfigMap::new(), messages);
         }
+    };
+    (table_to_map(input, document.as_table()), messages)
+}
+
+/// parses an override `<table>.<key>=<value>` (e.g. from the command line)
+/// into a `ConfigMap`, the value is taken as it is (not as toml value)
+pub fn parse_override(input: &str) -> Result<ConfigMap, Diagnostic> {
+    let invalid = || Diagnostic::new(
+        Location::default(),
+        format!("invalid override `{}`, expected `<table>.<key>=<value>`", input),
+    );
+    let (key, value) = input.split_once('=').ok_or_else(invalid)?;
+    let names: Vec<&str> = key.trim().split('.').map(str::trim).collect();
+    if names.iter().any(|name| name.is_empty()) {
+        return Err(invalid());
+    }
+    let mut element = ConfigElement::Setting(value.to_owned(), Location::default());
+    for name in names.iter().rev() {
+        let mut map = ConfigMap::new();
+        map.insert(name.to_string(), element);
+        element = ConfigElement::Nested(map, Location::default());
+    }
+    match element {
+        ConfigElement::Nested(map, _) => Ok(map),
+        _ => Err(invalid()),
+    }
+}
+
+/// reports the settings left in `config` (the table `parents`) after parsing
+/// as unknown, suggests the closest name in `known` if there is one
+pub fn unknown_settings(config: ConfigMap, parents: &str, known: &[&str]) -> Vec<Diagnostic> {
+    let full_key = |key: &str| if parents.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{}.{}", parents, key)
+    };
+    let mut messages: Vec<Diagnostic> = config.into_iter()
+        .map(|(key, element)| {
+            let message = match suggest(&key, known) {
+                Some(suggestion) => format!("unknown setting `{}`, did you mean `{}`?", full_key(&key), full_key(suggestion)),
+                None => format!("unknown setting `{}`", full_key(&key)),
+            };
+            Diagnostic::new(element.location(), message)
+        })
+        .collect();
+    messages.sort_by_key(|message| message.location);
+    messages
+}
+
+/// reports `element` as invalid value for the setting `key`, `expected`
+/// describes the values allowed
+pub fn invalid_value(element: &ConfigElement, key: &str, expected: &str) -> Diagnostic {
+    let message = match element {
+        ConfigElement::Setting(value, _) | ConfigElement::Scalar(value, _) => format!("invalid value `{}` for `{}`, expected {}", value, key, expected),
+        ConfigElement::Nested(..) => format!("invalid table for `{}`, expected {}", key, expected),
+        ConfigElement::Array(..) => format!("invalid array for `{}`, expected {}", key, expected),
+    };
+    Diagnostic::new(element.location(), message)
+}
+
+/// returns the candidate closest to `name`, if it is close enough to be a typo
+pub fn suggest<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let limit = (name.chars().count() / 3).max(1);
+    candidates.iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
 
+/// levenshtein distance of two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
     }
-    (config, messages)
+    previous[b.len()]
+}
+
+/// returns a toml string (quoted and escaped) containing `value`
+pub fn to_toml_string(value: &str) -> String {
+    Value::from(value).to_string()
 }
 
-/// searches for the first `#` not contained within a string
-/// and drops the rest of the line
-pub fn remove_inline_comment(line: &str) -> String {
-    let mut single_quotes: bool = false;
-    let mut double_quotes: bool = false;
-    let string: String = line.to_string();
+/// sets `key` (`<table>.<key>`) to the toml value `value` in the toml document
+/// `input`, tables are created if necessary
+/// comments and formatting of the document are kept
+pub fn set_toml_value(input: &str, key: &str, value: &str) -> Result<String, Diagnostic> {
+    let mut document = input.parse::<DocumentMut>().map_err(|error| {
+        let location = Location::from_span(input, error.span());
+        Diagnostic::new(location, error.message().trim_end().lines().collect::<Vec<&str>>().join(": "))
+    })?;
+    let mut value = value.parse::<Value>().map_err(|error| {
+        Diagnostic::new(Location::default(), format!("invalid toml value `{}`: {}", value, error.message().trim_end()))
+    })?;
+    let names: Vec<&str> = key.split('.').collect();
+    let Some((name, tables)) = names.split_last() else {
+        return Err(Diagnostic::new(Location::default(), format!("invalid key `{}`", key)));
+    };
+    // comments of a document without settings (e.g. a header) are kept in
+    // front of the first setting instead of being moved after it
+    let header: Option<String> = if document.is_empty() {
+        let header = document.trailing().as_str().map(|header| format!("{}\n", header));
+        document.set_trailing("");
+        header
+    } else {
+        None
+    };
 
-    for (n, character) in line.char_indices() {
-        match character {
-            '\'' => {
-                single_quotes = !single_quotes;
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+    for table_name in tables {
+        if !table.contains_key(table_name) {
+            table.insert(table_name, toml_edit::table());
+        }
+        table = match table.get_mut(table_name).and_then(Item::as_table_like_mut) {
+            Some(value) => value,
+            None => return Err(Diagnostic::new(Location::default(), format!("`{}` is not a table", table_name))),
+        };
+    }
+    // keep the comment of the value replaced
+    if let Some(Item::Value(old)) = table.get(name) {
+        *value.decor_mut() = old.decor().clone();
+    }
+    table.insert(name, Item::Value(value));
+    if let Some(header) = header {
+        match document.get_mut(names[0]) {
+            Some(Item::Table(table)) => table.decor_mut().set_prefix(header),
+            _ => if let Some(mut key) = document.key_mut(names[0]) {
+                key.leaf_decor_mut().set_prefix(header);
             },
-            '\"' => {
-                double_quotes = !double_quotes;
+        }
+    }
+    Ok(document.to_string())
+}
+
+fn table_to_map(input: &str, table: &Table) -> ConfigMap {
+    let mut map = ConfigMap::new();
+    for (name, _) in table.iter() {
+        let Some((key, item)) = table.get_key_value(name) else {
+            continue;
+        };
+        let location = key_location(input, key);
+        let element = match item {
+            Item::None => continue,
+            Item::Value(value) => value_to_element(input, value, location),
+            Item::Table(table) => ConfigElement::Nested(table_to_map(input, table), location),
+            Item::ArrayOfTables(array) => {
+                let tables = array.iter()
+                    .map(|table| ConfigElement::Nested(table_to_map(input, table), Location::from_span(input, table.span())))
+                    .collect();
+                ConfigElement::Array(tables, location)
             },
-            '#' => {
-                if single_quotes || double_quotes {
-                } else {
-                    return string[0..n].to_string();
+        };
+        map.insert(name.to_string(), element);
+    }
+    map
+}
+
+fn value_to_element(input: &str, value: &Value, location: Location) -> ConfigElement {
+    match value {
+        Value::String(string) => ConfigElement::Setting(string.value().clone(), location),
+        Value::Integer(integer) => ConfigElement::Scalar(integer.display_repr().into_owned(), location),
+        Value::Float(float) => ConfigElement::Scalar(float.display_repr().into_owned(), location),
+        Value::Boolean(boolean) => ConfigElement::Scalar(boolean.display_repr().into_owned(), location),
+        Value::Datetime(datetime) => ConfigElement::Scalar(datetime.display_repr().into_owned(), location),
+        Value::Array(array) => {
+            let elements = array.iter()
+                .map(|value| value_to_element(input, value, Location::from_span(input, value.span())))
+                .collect();
+            ConfigElement::Array(elements, location)
+        },
+        Value::InlineTable(table) => {
+            let mut map = ConfigMap::new();
+            for (name, _) in table.iter() {
+                if let Some((key, Item::Value(value))) = table.get_key_value(name) {
+                    map.insert(name.to_string(), value_to_element(input, value, key_location(input, key)));
                 }
-            },
-            _ => {
-                continue;
-            },
-        }
+            }
+            ConfigElement::Nested(map, location)
+        },
     }
-    string
+}
+
+fn key_location(input: &str, key: &Key) -> Location {
+    Location::from_span(input, key.span())
 }
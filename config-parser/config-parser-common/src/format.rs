@@ -1,6 +1,10 @@
 #![allow(dead_code)]
 
 use std::io::{Result, Error};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::common::{to_toml_string, ConfigElement};
+use super::value::ConfigValue;
 
 pub const ESC: &str = "\x1b";
 pub const PREFIX: &str = "\x1b[";
@@ -104,17 +108,152 @@ pub enum ColorContext {
     Background,
 }
 
-/// prepends input with style string and appends the reset sequence at the end
-pub fn apply_format(input: &String, style: &String) -> Result<String> {
-    let style_set: String = match parse_ansi_set(style) {
-        Ok(value) => value,
-        Err(error) => return Err(error),
-    };
-    let style_reset: String = match parse_ansi_unset(style) {
-        Ok(value) => value,
-        Err(error) => return Err(error),
-    };
-    Ok(format!("{}{}{}", style_set, input, style_reset))
+/// whether `apply_format` emits escape sequences, see `set_color`
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+/// when to style output with escape sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// style output displayed on a terminal, honors `NO_COLOR` and `CLICOLOR_FORCE`
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// parses `auto`, `always` or `never`, quotes are ignored
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().trim_matches(['\"', '\'']).to_lowercase().as_str() {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => Err(Error::other(format!("-- invalid color mode `{}`, expected `auto`, `always` or `never`", value))),
+        }
+    }
+
+    /// decides whether output displayed on `terminal` is styled
+    /// `NO_COLOR` and `CLICOLOR_FORCE` only take effect in `auto` mode
+    pub fn use_color(self, terminal: bool) -> bool {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let force = std::env::var_os("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0");
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                if no_color {
+                    false
+                } else if force {
+                    true
+                } else {
+                    terminal
+                }
+            },
+        }
+    }
+}
+
+impl ConfigValue for ColorMode {
+    fn from_element(element: &ConfigElement) -> Option<Self> {
+        match element {
+            ConfigElement::Setting(value, _) => ColorMode::parse(value).ok(),
+            _ => None,
+        }
+    }
+
+    fn to_toml(&self) -> String {
+        to_toml_string(&self.to_display())
+    }
+
+    fn to_display(&self) -> String {
+        match self {
+            ColorMode::Auto => "auto",
+            ColorMode::Always => "always",
+            ColorMode::Never => "never",
+        }.to_owned()
+    }
+
+    fn expected() -> String {
+        "one of `auto`, `always` or `never`".to_owned()
+    }
+
+    fn variants() -> Vec<String> {
+        vec!["auto".to_owned(), "always".to_owned(), "never".to_owned()]
+    }
+}
+
+/// enables or disables escape sequences in `apply_format` for the whole process
+pub fn set_color(enabled: bool) {
+    COLOR.store(enabled, Ordering::Relaxed);
+}
+
+/// returns whether `apply_format` emits escape sequences
+pub fn color_enabled() -> bool {
+    COLOR.load(Ordering::Relaxed)
+}
+
+/// style of a setting (e.g. `red, bold`), the escape sequences are generated
+/// once when the setting is parsed, the default style leaves text as it is
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Style {
+    source: String,
+    set: String,
+    reset: String,
+}
+
+impl Style {
+    /// parses styles and one color separated by commas or spaces
+    pub fn parse(value: &str) -> Result<Self> {
+        let source = value.to_owned();
+        let set = parse_ansi_set(&source)?;
+        let reset = parse_ansi_unset(&source)?;
+        Ok(Self { source, set, reset })
+    }
+
+    /// escape sequence starting the style
+    pub fn start(&self) -> &str {
+        &self.set
+    }
+
+    /// escape sequence resetting the style
+    pub fn end(&self) -> &str {
+        &self.reset
+    }
+}
+
+impl ConfigValue for Style {
+    fn from_element(element: &ConfigElement) -> Option<Self> {
+        match element {
+            ConfigElement::Setting(value, _) => Style::parse(value).ok(),
+            _ => None,
+        }
+    }
+
+    fn to_toml(&self) -> String {
+        to_toml_string(&self.source)
+    }
+
+    fn to_display(&self) -> String {
+        self.source.clone()
+    }
+
+    fn expected() -> String {
+        "styles and one color separated by commas, e.g. `red, bold`".to_owned()
+    }
+}
+
+/// prepends input with the style sequence and appends the reset sequence
+/// returns the input as it is if colors are disabled (see `set_color`)
+pub fn apply_format(input: &str, style: &Style) -> String {
+    apply_format_with(input, style, color_enabled())
+}
+
+/// same as `apply_format`, but styles only if `color` is true
+pub fn apply_format_with(input: &str, style: &Style, color: bool) -> String {
+    if !color {
+        return input.to_owned();
+    }
+    format!("{}{}{}", style.start(), input, style.end())
 }
 
 /// generates a common style sequence of format
@@ -269,13 +408,13 @@ pub fn parse_ansi_unset(arg: &String) -> Result<String> {
     // parse options
     for option in tokens {
         // parse numbered colors
-        if let Ok(_) = parse_numbered_color(&option) {
+        if parse_numbered_color(&option).is_ok() {
             colors.push(generate_style_sequence(None, Some(COLORS.fg.default), None));
             continue;
         }
 
         // parse rgb colors
-        if let Ok(_) = parse_rgb_color(&option) {
+        if parse_rgb_color(&option).is_ok() {
             colors.push(generate_style_sequence(None, Some(COLORS.fg.default), None));
             continue;
         }
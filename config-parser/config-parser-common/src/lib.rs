@@ -1,11 +1,26 @@
 
 pub mod common;
 pub mod format;
+pub mod value;
 
 pub use common::{
     ConfigElement,
     ConfigMap,
+    Diagnostic,
+    Location,
+    invalid_value,
     parse_config_file,
-    remove_inline_comment,
+    parse_override,
+    set_toml_value,
+    suggest,
+    to_toml_string,
+    unknown_settings,
 };
 
+pub use value::{
+    ConfigValue,
+    check_non_empty,
+    check_one_of,
+    check_range,
+    describe_range,
+};
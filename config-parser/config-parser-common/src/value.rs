@@ -0,0 +1,218 @@
+//! conversion between the elements of a configuration and the types of
+//! the fields of a config struct
+
+use std::fmt::Display;
+use std::ops::{Bound, RangeBounds};
+use std::path::PathBuf;
+
+use super::common::{to_toml_string, ConfigElement};
+
+/// type of a field of a config struct
+/// implemented for `bool`, numbers, `String`, `PathBuf`, `Option<T>`, `Vec<T>`
+/// and enums deriving `ConfigEnum`
+pub trait ConfigValue: Sized {
+    /// parses the value from an element of a configuration layer (file,
+    /// environment variable or override), returns `None` if the element is invalid
+    fn from_element(element: &ConfigElement) -> Option<Self>;
+
+    /// writes the value as toml, e.g. for the default configuration file
+    fn to_toml(&self) -> String;
+
+    /// writes the value as it is shown to the user
+    fn to_display(&self) -> String;
+
+    /// description of the expected values, used in error messages
+    fn expected() -> String;
+
+    /// values allowed for the type, listed in the default configuration file
+    fn variants() -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// implements `ConfigValue` for types parsed with `FromStr` from a single value
+macro_rules! impl_from_str {
+    ($expected:literal, $($ty:ty),+) => {
+        $(
+            impl ConfigValue for $ty {
+                fn from_element(element: &ConfigElement) -> Option<Self> {
+                    match element {
+                        ConfigElement::Setting(value, _) | ConfigElement::Scalar(value, _) => value.trim().parse::<$ty>().ok(),
+                        _ => None,
+                    }
+                }
+
+                fn to_toml(&self) -> String {
+                    self.to_string()
+                }
+
+                fn to_display(&self) -> String {
+                    self.to_string()
+                }
+
+                fn expected() -> String {
+                    $expected.to_owned()
+                }
+            }
+        )+
+    };
+}
+
+impl_from_str!("a boolean (`true` or `false`)", bool);
+impl_from_str!("an integer", u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+impl_from_str!("a number", f32, f64);
+
+/// toml strings only, numbers and booleans (e.g. `1` in `[1, 2]`) are
+/// rejected, environment variables and overrides are taken as text
+impl ConfigValue for String {
+    fn from_element(element: &ConfigElement) -> Option<Self> {
+        match element {
+            ConfigElement::Setting(value, _) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn to_toml(&self) -> String {
+        to_toml_string(self)
+    }
+
+    fn to_display(&self) -> String {
+        self.clone()
+    }
+
+    fn expected() -> String {
+        "a string".to_owned()
+    }
+}
+
+/// a leading `~` is expanded to the home directory
+impl ConfigValue for PathBuf {
+    fn from_element(element: &ConfigElement) -> Option<Self> {
+        let value = String::from_element(element)?;
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        match (value.strip_prefix('~'), home) {
+            (Some(""), Some(home)) => Some(home),
+            (Some(rest), Some(home)) if rest.starts_with('/') => Some(home.join(rest.trim_start_matches('/'))),
+            _ => Some(PathBuf::from(value)),
+        }
+    }
+
+    fn to_toml(&self) -> String {
+        to_toml_string(&self.to_string_lossy())
+    }
+
+    fn to_display(&self) -> String {
+        self.to_string_lossy().into_owned()
+    }
+
+    fn expected() -> String {
+        "a path".to_owned()
+    }
+}
+
+/// an empty string (`""`) or `none` unsets the value, toml has no null value
+impl<T: ConfigValue> ConfigValue for Option<T> {
+    fn from_element(element: &ConfigElement) -> Option<Self> {
+        match element {
+            ConfigElement::Setting(value, _) if value.is_empty() || value.eq_ignore_ascii_case("none") => Some(None),
+            _ => T::from_element(element).map(Some),
+        }
+    }
+
+    fn to_toml(&self) -> String {
+        match self {
+            Some(value) => value.to_toml(),
+            None => to_toml_string(""),
+        }
+    }
+
+    fn to_display(&self) -> String {
+        match self {
+            Some(value) => value.to_display(),
+            None => String::new(),
+        }
+    }
+
+    fn expected() -> String {
+        format!("{} or an empty string", T::expected())
+    }
+
+    fn variants() -> Vec<String> {
+        T::variants()
+    }
+}
+
+/// toml arrays, a single value (environment variables and overrides) is
+/// split at commas
+impl<T: ConfigValue> ConfigValue for Vec<T> {
+    fn from_element(element: &ConfigElement) -> Option<Self> {
+        match element {
+            ConfigElement::Array(elements, _) => elements.iter().map(T::from_element).collect(),
+            ConfigElement::Setting(value, location) => value.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| T::from_element(&ConfigElement::Setting(item.to_owned(), *location)))
+                .collect(),
+            ConfigElement::Scalar(..) => T::from_element(element).map(|value| vec![value]),
+            ConfigElement::Nested(..) => None,
+        }
+    }
+
+    fn to_toml(&self) -> String {
+        format!("[{}]", self.iter().map(T::to_toml).collect::<Vec<String>>().join(", "))
+    }
+
+    fn to_display(&self) -> String {
+        self.iter().map(T::to_display).collect::<Vec<String>>().join(", ")
+    }
+
+    fn expected() -> String {
+        format!("an array of {}", T::expected())
+    }
+
+    fn variants() -> Vec<String> {
+        T::variants()
+    }
+}
+
+/// checks `#[range(..)]`, returns the values expected if `value` is out of `range`
+pub fn check_range<T: PartialOrd + Display, R: RangeBounds<T>>(value: &T, range: R) -> Result<(), String> {
+    if range.contains(value) {
+        Ok(())
+    } else {
+        Err(format!("a value in `{}`", describe_range(&range)))
+    }
+}
+
+/// writes a range as in rust, e.g. `1..=1000`
+pub fn describe_range<T: Display, R: RangeBounds<T>>(range: &R) -> String {
+    let start = match range.start_bound() {
+        Bound::Included(value) | Bound::Excluded(value) => value.to_string(),
+        Bound::Unbounded => String::new(),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(value) => format!("={}", value),
+        Bound::Excluded(value) => value.to_string(),
+        Bound::Unbounded => String::new(),
+    };
+    format!("{}..{}", start, end)
+}
+
+/// checks `#[one_of(..)]`, the value is compared as it is shown to the user
+pub fn check_one_of<T: ConfigValue>(value: &T, allowed: &[&str]) -> Result<(), String> {
+    if allowed.contains(&value.to_display().as_str()) {
+        Ok(())
+    } else {
+        Err(format!("one of {}", allowed.iter().map(|item| format!("`{}`", item)).collect::<Vec<String>>().join(", ")))
+    }
+}
+
+/// checks `#[non_empty]`, i.e. strings, paths and arrays with content and
+/// options which are set
+pub fn check_non_empty<T: ConfigValue>(value: &T) -> Result<(), String> {
+    if value.to_display().is_empty() {
+        Err("a non-empty value".to_owned())
+    } else {
+        Ok(())
+    }
+}
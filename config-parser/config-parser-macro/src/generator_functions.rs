@@ -1,49 +1,136 @@
 use proc_macro2::{Ident, TokenStream};
-use syn::{punctuated::Punctuated, token::Comma, Attribute, Expr, ExprLit, Field, Meta, MetaList, MetaNameValue, Path};
+use syn::{punctuated::Punctuated, token::Comma, Attribute, Expr, ExprLit, Field, Lit, LitStr, Meta, MetaList, MetaNameValue, Path};
+use config_parser_common::format::Style;
 use quote::quote;
 
-pub fn gen_parse_from_string(config_name: &Ident, output_name: &Ident, assignments: &TokenStream) -> TokenStream {
+pub fn gen_parse_from_string() -> TokenStream {
     quote! {
-        /// tries to parse config from a string
-        /// if **convert_styles** == true, the settings marked with
-        /// `style_config` are converted to ansi escape sequences to
-        /// style terminal ouput
-        pub fn parse_from_string(&mut self, input: &String) -> std::io::Result<()> {
-            let (mut #config_name, mut #output_name) : (ConfigMap, Vec<String>) = parse_config_file(input);
-
-            #assignments
-
-            if !#config_name.is_empty() {
-                let leftovers = #config_name.keys().cloned().collect::<Vec<String>>();
-                #output_name.push(format!("the following settings were not recognised: {:#?}", leftovers));
-            }
-            if !#output_name.is_empty() {
-                return Err(std::io::Error::other(format!("{}", #output_name.join("\n"))));
+        /// tries to parse config from a string, settings which are not
+        /// defined keep their value
+        /// returns the syntax errors, unknown settings and invalid values found
+        pub fn parse_from_string(&mut self, input: &str) -> std::result::Result<(), Vec<Diagnostic>> {
+            let (_, diagnostics) = self.merge_from_string(input);
+            if !diagnostics.is_empty() {
+                return Err(diagnostics);
             }
             Ok(())
         }
+
+        /// merges a configuration layer (e.g. a file) into the config, only the
+        /// settings defined in `input` are overwritten
+        /// returns the keys (`<table>.<key>`) of the settings which were set
+        /// and the syntax errors, unknown settings and invalid values found
+        pub fn merge_from_string(&mut self, input: &str) -> (Vec<String>, Vec<Diagnostic>) {
+            let (map, mut diagnostics) : (ConfigMap, Vec<Diagnostic>) = parse_config_file(input);
+            let mut keys = Vec::<String>::new();
+            diagnostics.extend(self.merge_from_map(map, "", &mut keys));
+            diagnostics.sort_by_key(|message| message.location);
+            (keys, diagnostics)
+        }
     }
 }
 
-pub fn gen_parse_from_map(config_name: &Ident, output_name: &Ident, assignments: &TokenStream) -> TokenStream {
+pub fn gen_merge_from_map(config_name: &Ident, output_name: &Ident, assignments: &TokenStream, names: &TokenStream) -> TokenStream {
     quote! {
         /// **do not call**
         /// this function needs to be public for nested configs but is not intended
         /// to be called by the user
-        pub fn parse_from_map(&mut self, input: ConfigMap) -> std::io::Result<()> {
+        pub fn merge_from_map(&mut self, input: ConfigMap, parents: &str, keys: &mut Vec<String>) -> Vec<Diagnostic> {
             let mut #config_name: ConfigMap = input;
-            let mut #output_name: Vec<String> = Vec::<String>::new();
+            let mut #output_name: Vec<Diagnostic> = Vec::<Diagnostic>::new();
 
             #assignments
 
-            if !#config_name.is_empty() {
-                let leftovers = #config_name.keys().cloned().collect::<Vec<String>>();
-                #output_name.push(format!("the following settings were not recognised: {:#?}", leftovers));
+            #output_name.extend(unknown_settings(#config_name, parents, &[#names]));
+            #output_name
+        }
+    }
+}
+
+/// lists the names of all settings and nested configs, used to suggest
+/// the correct name for unknown settings
+pub fn gen_names(fields: &Punctuated<Field, Comma>) -> TokenStream {
+    let mut names: TokenStream = TokenStream::new();
+    'fields: for field in fields.iter() {
+        let name = match &field.ident {
+            Some(value) => value,
+            None => continue 'fields,
+        };
+        for attribute in &field.attrs {
+            if let Attribute{ meta: Meta::Path( Path{segments, ..} ), .. } = attribute {
+                if segments.first().is_some_and(|segment| segment.ident == "no_config") {
+                    continue 'fields;
+                }
+            }
+        }
+        let name_string = name.to_string();
+        names.extend(quote!{ #name_string, });
+    }
+    names
+}
+
+pub fn gen_apply_env(fields: &Punctuated<Field, Comma>) -> TokenStream {
+    let mut statements: TokenStream = TokenStream::new();
+    'fields: for field in fields.iter() {
+        let name = match &field.ident {
+            Some(value) => value,
+            None => continue 'fields,
+        };
+        let name_string = name.to_string();
+        let name_upper = name_string.to_uppercase();
+        for attribute in &field.attrs {
+            if let Attribute{ meta: Meta::Path( Path{segments, ..} ), .. } = attribute {
+                if let Some(attr_name) = segments.first() {
+                    if attr_name.ident == "nested_config" {
+                        statements.extend(quote! {
+                            self.#name.apply_env_nested(
+                                &format!("{}{}__", variable, #name_upper),
+                                &Self::entry_key(parents, #name_string),
+                                keys,
+                                output,
+                            );
+                        });
+                        continue 'fields;
+                    } else if attr_name.ident == "no_config" {
+                        continue 'fields;
+                    }
+                }
             }
-            if !#output_name.is_empty() {
-                return Err(std::io::Error::other(format!("{}", #output_name.join("\n"))));
+        }
+        // same parsing as overrides, arrays are separated by commas
+        let (parse, _) = gen_constraints(field);
+        statements.extend(quote! {
+            let name = format!("{}{}", variable, #name_upper);
+            if let Ok(value) = std::env::var(&name) {
+                let element = ConfigElement::Setting(value.clone(), Location::default());
+                match #parse {
+                    Ok(parsed) => {
+                        self.#name = parsed;
+                        keys.push(Self::entry_key(parents, #name_string));
+                    },
+                    Err(expected) => output.push(Diagnostic::new(
+                        Location::default(),
+                        format!("invalid value `{}` in `{}`, expected {}", value, name, expected),
+                    )),
+                }
             }
-            Ok(())
+        });
+    }
+    quote! {
+        /// overrides settings with environment variables named
+        /// `<prefix>_<TABLE>__<KEY>`, e.g. `NAVIGATE_GENERAL__DEDUP_STACK`
+        /// returns the keys (`<table>.<key>`) of the settings which were set
+        /// and the malformed values found
+        pub fn apply_env(&mut self, prefix: &str) -> (Vec<String>, Vec<Diagnostic>) {
+            let mut keys = Vec::<String>::new();
+            let mut output = Vec::<Diagnostic>::new();
+            self.apply_env_nested(&format!("{}_", prefix), "", &mut keys, &mut output);
+            (keys, output)
+        }
+
+        /// macro function - do not call
+        pub fn apply_env_nested(&mut self, variable: &str, parents: &str, keys: &mut Vec<String>, output: &mut Vec<Diagnostic>) {
+            #statements
         }
     }
 }
@@ -58,6 +145,7 @@ pub fn gen_default(fields: &Punctuated<Field, Comma>) -> TokenStream {
             None => continue 'fields,
         };
         let ty = &field.ty;
+        let style = attr.iter().any(|attribute| attribute.path().is_ident("style_config"));
         for attribute in attr {
             match attribute {
                 Attribute { meta: Meta::Path(Path{segments, ..}), .. } => {
@@ -69,6 +157,10 @@ pub fn gen_default(fields: &Punctuated<Field, Comma>) -> TokenStream {
                         defaults.extend(quote!{
                             #name: #ty::default(),
                         });
+                    } else if attr_name.ident == "no_config" {
+                        defaults.extend(quote!{
+                            #name: Default::default(),
+                        });
                     }
                 },
                 Attribute { meta: Meta::List(MetaList{ path: Path{ segments, .. }, tokens, .. }), .. } => {
@@ -76,7 +168,9 @@ pub fn gen_default(fields: &Punctuated<Field, Comma>) -> TokenStream {
                         Some(value) => value,
                         None => panic!("no valid attribute found!"),
                     };
-                    if attr_name.ident == "default_value" {
+                    if attr_name.ident == "default_value" && style {
+                        defaults.extend(gen_default_style(name, tokens));
+                    } else if attr_name.ident == "default_value" {
                         defaults.extend(quote!{
                             #name: #tokens.into(),
                         });
@@ -96,10 +190,21 @@ pub fn gen_default(fields: &Punctuated<Field, Comma>) -> TokenStream {
     }
 }
 
+/// default styles are checked at compile time, so parsing them cannot fail
+fn gen_default_style(name: &Ident, tokens: &TokenStream) -> TokenStream {
+    let error = match syn::parse2::<LitStr>(tokens.clone()) {
+        Ok(style) => match Style::parse(&style.value()) {
+            Ok(_) => return quote!{ #name: Style::parse(#style).unwrap_or_default(), },
+            Err(error) => format!("invalid default style `{}`: {}", style.value(), error.to_string().trim_start_matches("-- ")),
+        },
+        Err(_) => "the default value of a style has to be a string".to_owned(),
+    };
+    quote!{ #name: compile_error!(#error), }
+}
+
 pub fn gen_to_string(fields: &Punctuated<Field, Comma>) -> TokenStream {
     let mut statements: TokenStream = TokenStream::new();
     let mut nested_statements: TokenStream = TokenStream::new();
-    let mut comment: TokenStream = TokenStream::new();
     'fields: for field in fields.iter() {
         let attr = &field.attrs;
         let name = match &field.ident {
@@ -107,26 +212,24 @@ pub fn gen_to_string(fields: &Punctuated<Field, Comma>) -> TokenStream {
             None => continue 'fields,
         };
         let name_string = name.to_string();
+        let mut comment: TokenStream = TokenStream::new();
         for attribute in attr {
             if let Attribute{ meta: Meta::Path( Path{segments, ..} ), .. } = attribute {
                 // parse nested configs or skip nonconfig elements
-                match segments.first() {
-                    Some(attr_name) => {
-                        if attr_name.ident == "nested_config" {
-                            statements.extend(quote! {
-                                string.push_str(&format!("\n[{}]\n", #name_string));
-                                string.push_str(&self.#name.to_string_nested(&format!("{}", #name_string)));
-                            });
-                            nested_statements.extend(quote! {
-                                string.push_str(&format!("\n[{}.{}]\n", parents, #name_string));
-                                string.push_str(&self.#name.to_string_nested(&format!("{}.{}", parents, #name_string)));
-                            });
-                            continue 'fields;
-                        } else if attr_name.ident == "no_config" {
-                            continue 'fields;
-                        }
-                    },
-                    None => (),
+                if let Some(attr_name) = segments.first() {
+                    if attr_name.ident == "nested_config" {
+                        statements.extend(quote! {
+                            string.push_str(&format!("\n[{}]\n", #name_string));
+                            string.push_str(&self.#name.to_string_nested(&format!("{}", #name_string)));
+                        });
+                        nested_statements.extend(quote! {
+                            string.push_str(&format!("\n[{}.{}]\n", parents, #name_string));
+                            string.push_str(&self.#name.to_string_nested(&format!("{}.{}", parents, #name_string)));
+                        });
+                        continue 'fields;
+                    } else if attr_name.ident == "no_config" {
+                        continue 'fields;
+                    }
                 }
             } else if let Attribute{ meta: Meta::NameValue( MetaNameValue{path: Path{ segments, .. }, value: Expr::Lit(ExprLit{lit, ..}), ..} ), .. } = attribute {
                 // write comments to string
@@ -139,10 +242,21 @@ pub fn gen_to_string(fields: &Punctuated<Field, Comma>) -> TokenStream {
             }
         }
         if comment.is_empty() {
-            comment.extend(quote! {""});
+            comment.extend(quote! {String::new()});
         }
+        // possible values (enums and `one_of`) and constraints are listed
+        // after the comment
+        let (_, describe) = gen_constraints(field);
         let code = quote!{
-            string.push_str(&format!("{} = {}{}\n", #name_string, self.#name.to_string(), #comment));
+            let mut comment: String = #comment;
+            #describe
+            if !constraints.is_empty() && comment.is_empty() {
+                comment.push_str(" #");
+            }
+            for constraint in constraints {
+                comment.push_str(&format!(" [{}]", constraint));
+            }
+            string.push_str(&format!("{} = {}{}\n", #name_string, ConfigValue::to_toml(&self.#name), comment));
         };
         statements.extend(code.clone());
         nested_statements.extend(code);
@@ -155,7 +269,7 @@ pub fn gen_to_string(fields: &Punctuated<Field, Comma>) -> TokenStream {
         }
 
         /// macro function - do not call
-        pub fn to_string_nested(&self, parents: &String) -> String {
+        pub fn to_string_nested(&self, parents: &str) -> String {
             let mut string = String::new();
             #nested_statements
             string
@@ -163,6 +277,69 @@ pub fn gen_to_string(fields: &Punctuated<Field, Comma>) -> TokenStream {
     }
 }
 
+pub fn gen_to_entries(fields: &Punctuated<Field, Comma>) -> TokenStream {
+    let mut statements: TokenStream = TokenStream::new();
+    'fields: for field in fields.iter() {
+        let attr = &field.attrs;
+        let name = match &field.ident {
+            Some(value) => value,
+            None => continue 'fields,
+        };
+        let name_string = name.to_string();
+        for attribute in attr {
+            if let Attribute{ meta: Meta::Path( Path{segments, ..} ), .. } = attribute {
+                // collect entries of nested configs or skip nonconfig elements
+                if let Some(attr_name) = segments.first() {
+                    if attr_name.ident == "nested_config" {
+                        statements.extend(quote! {
+                            entries.extend(self.#name.to_entries_nested(&Self::entry_key(parents, #name_string), toml));
+                        });
+                        continue 'fields;
+                    } else if attr_name.ident == "no_config" {
+                        continue 'fields;
+                    }
+                }
+            }
+        }
+        statements.extend(quote!{
+            let value = if toml {
+                ConfigValue::to_toml(&self.#name)
+            } else {
+                ConfigValue::to_display(&self.#name)
+            };
+            entries.push((Self::entry_key(parents, #name_string), value));
+        });
+    }
+    quote! {
+        /// returns all settings as `(<table>.<key>, <value>)` pairs
+        pub fn to_entries(&self) -> Vec<(String, String)> {
+            self.to_entries_nested("", false)
+        }
+
+        /// returns all settings as `(<table>.<key>, <toml value>)` pairs,
+        /// i.e. strings are quoted and escaped
+        pub fn to_toml_entries(&self) -> Vec<(String, String)> {
+            self.to_entries_nested("", true)
+        }
+
+        /// macro function - do not call
+        pub fn to_entries_nested(&self, parents: &str, toml: bool) -> Vec<(String, String)> {
+            let mut entries = Vec::<(String, String)>::new();
+            #statements
+            entries
+        }
+
+        /// macro function - do not call
+        fn entry_key(parents: &str, name: &str) -> String {
+            if parents.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{}.{}", parents, name)
+            }
+        }
+    }
+}
+
 pub fn gen_config_assignments(fields: &Punctuated<Field, Comma>, config_map_name: &Ident, output_name: &Ident) -> TokenStream {
     let mut assignments: TokenStream = TokenStream::new();
     'fields: for field in fields.iter() {
@@ -173,56 +350,163 @@ pub fn gen_config_assignments(fields: &Punctuated<Field, Comma>, config_map_name
             None => continue 'fields,
         };
         let name_string: String = name.to_string();
-        let ty = &field.ty;
         for attribute in attr {
             if let Attribute{ meta: Meta::Path( Path{segments, ..} ), .. } = attribute {
-                match segments.first() {
-                    Some(attr_name) => {
-                        if attr_name.ident == "nested_config" {
-                            assignments.extend(quote! {
-                                match #config_map_name.remove(#name_string) {
-                                    Some(ConfigElement::Nested(map)) => {
-                                        if let Err(error) = self.#name.parse_from_map(map) {
-                                            #output_name.push(error.to_string());
-                                        }
-                                    },
-                                    Some(ConfigElement::Setting(_)) => #output_name.push(format!("`{}` is defined as a nested element, but the configuration file defines it a setting element", #name_string)),
-                                    None => #output_name.push(format!("no table `{}` found in config file", #name_string)),
-                                }
-                            });
-                            continue 'fields;
-                        } else if attr_name.ident == "no_config" {
-                            continue 'fields;
-                        }
-                    },
-                    None => (),
+                if let Some(attr_name) = segments.first() {
+                    if attr_name.ident == "nested_config" {
+                        // missing tables keep their values
+                        assignments.extend(quote! {
+                            match #config_map_name.remove(#name_string) {
+                                Some(ConfigElement::Nested(map, _)) => {
+                                    #output_name.extend(self.#name.merge_from_map(map, &Self::entry_key(parents, #name_string), keys));
+                                },
+                                Some(element) => #output_name.push(Diagnostic::new(
+                                    element.location(),
+                                    format!("`{}` has to be a table", Self::entry_key(parents, #name_string)),
+                                )),
+                                None => (),
+                            }
+                        });
+                        continue 'fields;
+                    } else if attr_name.ident == "no_config" {
+                        continue 'fields;
+                    }
                 }
             }
             //} else if let Attribute{ meta: Meta::List()}
         }
+        // missing settings keep their values, invalid values are reported
+        // with the values expected by the type or the constraints
+        let (parse, _) = gen_constraints(field);
         assignments.extend(quote! {
-            match #config_map_name.remove(#name_string) {
-                Some(ConfigElement::Setting(value)) => {
-                    self.#name = match value.parse::<#ty>() {
-                        Ok(parsed) => {
-                            parsed
-                        },
-                        Err(_) => {
-                            #output_name.push(format!("failed to parse value found for `{}`", #name_string));
-                            self.#name.clone()
-                        },
-                    };
-                },
-                Some(ConfigElement::Nested(_)) => {
-                    #output_name.push(format!("`{}` is a setting element, but the configuration file defines it as a nested element (Table)", #name_string));
-                },
-                None => {
-                    #output_name.push(format!("could not find `{}` in config file", #name_string));
-                    self.#name = self.#name.clone();
-                },
-            };
+            if let Some(element) = #config_map_name.remove(#name_string) {
+                match #parse {
+                    Ok(parsed) => {
+                        self.#name = parsed;
+                        keys.push(Self::entry_key(parents, #name_string));
+                    },
+                    Err(expected) => #output_name.push(invalid_value(
+                        &element,
+                        &Self::entry_key(parents, #name_string),
+                        &expected,
+                    )),
+                };
+            }
         });
     }
     assignments
 }
 
+/// implements `ConfigValue` for an enum of unit variants, the variants are
+/// written in snake case (`ConsecutiveOnly` -> `consecutive_only`)
+pub fn gen_config_enum(enum_name: &Ident, variants: &[Ident]) -> TokenStream {
+    let names: Vec<String> = variants.iter().map(|variant| to_snake_case(&variant.to_string())).collect();
+    let expected = format!(
+        "one of {}",
+        names.iter().map(|name| format!("`{}`", name)).collect::<Vec<String>>().join(", "),
+    );
+    quote! {
+        impl ConfigValue for #enum_name {
+            fn from_element(element: &ConfigElement) -> Option<Self> {
+                match element {
+                    ConfigElement::Setting(value, _) => match value.trim().to_lowercase().as_str() {
+                        #( #names => Some(Self::#variants), )*
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+
+            fn to_toml(&self) -> String {
+                to_toml_string(&self.to_display())
+            }
+
+            fn to_display(&self) -> String {
+                match self {
+                    #( Self::#variants => #names, )*
+                }.to_owned()
+            }
+
+            fn expected() -> String {
+                #expected.to_owned()
+            }
+
+            fn variants() -> Vec<String> {
+                vec![#( #names.to_owned(), )*]
+            }
+        }
+    }
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (n, character) in name.chars().enumerate() {
+        if character.is_uppercase() && n > 0 {
+            snake.push('_');
+        }
+        snake.extend(character.to_lowercase());
+    }
+    snake
+}
+
+/// collects the constraints of a field (`range`, `one_of`, `non_empty` and
+/// `validate_with`), returns the expression parsing `element` and checking
+/// the constraints (`Result<#ty, <expected values>>`) and the statements
+/// describing the constraints in the default configuration
+fn gen_constraints(field: &Field) -> (TokenStream, TokenStream) {
+    let ty = &field.ty;
+    let mut checks: TokenStream = TokenStream::new();
+    let mut descriptions: TokenStream = TokenStream::new();
+    let mut variants: TokenStream = quote!{ <#ty as ConfigValue>::variants() };
+    for attribute in &field.attrs {
+        match &attribute.meta {
+            Meta::Path(path) if path.is_ident("non_empty") => {
+                checks.extend(quote!{ .and_then(|()| check_non_empty(&parsed)) });
+                descriptions.extend(quote!{ constraints.push("non-empty".to_owned()); });
+            },
+            Meta::List(MetaList{ path, tokens, .. }) if path.is_ident("range") => {
+                checks.extend(quote!{ .and_then(|()| check_range(&parsed, #tokens)) });
+                descriptions.extend(quote!{ constraints.push(format!("range: {}", describe_range::<#ty, _>(&(#tokens)))); });
+            },
+            Meta::List(MetaList{ path, tokens, .. }) if path.is_ident("one_of") => {
+                checks.extend(quote!{ .and_then(|()| check_one_of(&parsed, &[#tokens])) });
+                variants = quote!{ [#tokens].iter().map(|item: &&str| item.to_string()).collect::<Vec<String>>() };
+            },
+            // attribute values have to be literals, so the path is given as string
+            Meta::NameValue(MetaNameValue{ path, value: Expr::Lit(ExprLit{ lit: Lit::Str(function), .. }), .. }) if path.is_ident("validate_with") => {
+                let function: Path = function.parse().expect("`validate_with` expects the path of a function");
+                checks.extend(quote!{ .and_then(|()| #function(&parsed)) });
+                descriptions.extend(describe_validator(&quote!{ #function }));
+            },
+            Meta::List(MetaList{ path, tokens, .. }) if path.is_ident("validate_with") => {
+                checks.extend(quote!{ .and_then(|()| #tokens(&parsed)) });
+                descriptions.extend(describe_validator(tokens));
+            },
+            _ => (),
+        }
+    }
+    let parse = quote!{
+        <#ty as ConfigValue>::from_element(&element)
+            .ok_or_else(<#ty as ConfigValue>::expected)
+            .and_then(|parsed| {
+                let valid: std::result::Result<(), String> = Ok(()) #checks;
+                valid.map(|()| parsed)
+            })
+    };
+    let describe = quote!{
+        let mut constraints = Vec::<String>::new();
+        let variants: Vec<String> = #variants;
+        if !variants.is_empty() {
+            constraints.push(format!("possible values: {}", variants.join(", ")));
+        }
+        #descriptions
+    };
+    (parse, describe)
+}
+
+/// lists a `validate_with` function by its name, e.g. `checked by even`
+fn describe_validator(function: &TokenStream) -> TokenStream {
+    let name: String = function.to_string().replace(' ', "");
+    let description: String = format!("checked by {}", name);
+    quote!{ constraints.push(#description.to_owned()); }
+}
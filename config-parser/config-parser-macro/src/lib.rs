@@ -7,17 +7,47 @@ use quote::quote;
 use generator_functions::*;
 
 /// **for structs only**
-/// - implements `parse_config(&mut self, input: &String) -> Result<()>`
-/// which parses a string and fills the fills recognised values into the struct
-/// - implements `write_default_config() -> Result<String>`
-/// which write a default configuration, in case the documentation is lacking
+/// - implements `parse_from_string(&mut self, input: &str) -> Result<(), Vec<Diagnostic>>`
+///   which parses a string and fills the recognised values into the struct,
+///   syntax errors, unknown settings and invalid values are returned as diagnostics
+/// - implements `merge_from_string(&mut self, input: &str) -> (Vec<String>, Vec<Diagnostic>)`
+///   which overwrites only the settings defined in the input (configuration layers)
+///   and returns their keys along with the diagnostics
+/// - implements `apply_env(&mut self, prefix: &str) -> (Vec<String>, Vec<Diagnostic>)`
+///   which overrides settings with environment variables `<prefix>_<TABLE>__<KEY>`
+/// - implements `to_string(&self) -> String`
+///   which writes a default configuration, in case the documentation is lacking
+/// - implements `to_entries(&self) -> Vec<(String, String)>`
+///   which lists all settings with their keys (`<table>.<key>`)
+/// - implements `to_toml_entries(&self) -> Vec<(String, String)>`
+///   which lists all settings with their values written as toml
+///
+/// fields marked `#[style_config]` have the type `Style`, their default value
+/// (e.g. `#[default_value("red, bold")]`) is checked at compile time
+///
+/// fields have to implement `ConfigValue`, i.e. `bool`, numbers, `String`,
+/// `PathBuf` (`~` is expanded), `Option<T>`, `Vec<T>` (toml arrays) and
+/// enums deriving `ConfigEnum`
+///
+/// values can be constrained, invalid values are reported with the values
+/// expected and the constraints are listed in the default configuration
+/// - `#[range(1..=1000)]` the value (a number) has to be in the range
+/// - `#[one_of("auto", "always", "never")]` the value has to be one of the strings
+/// - `#[non_empty]` strings, paths and arrays must not be empty, options must be set
+/// - `#[validate_with = "path::to::function"]` calls `fn(&T) -> Result<(), String>`,
+///   the error describes the values expected, e.g. `"an existing directory"`,
+///   the function is listed as `checked by <function>`
 #[proc_macro_derive(
     ConfigParser,
     attributes(
         default_value,
         nested_config,
         no_config,
-        style_config
+        style_config,
+        range,
+        one_of,
+        non_empty,
+        validate_with
     )
 )]
 pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -31,18 +61,43 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         panic!("the macro `ConfigParser` applies only to structs!");
     };
     let assignments: TokenStream = gen_config_assignments(fields, &config_name, &output_name);
-    let func_parse_string: TokenStream = gen_parse_from_string(&config_name, &output_name, &assignments);
-    let func_parse_map: TokenStream = gen_parse_from_map(&config_name, &output_name, &assignments);
+    let names: TokenStream = gen_names(fields);
+    let func_parse_string: TokenStream = gen_parse_from_string();
+    let func_merge_map: TokenStream = gen_merge_from_map(&config_name, &output_name, &assignments, &names);
     let func_default: TokenStream = gen_default(fields);
     let func_to_string: TokenStream = gen_to_string(fields);
+    let func_to_entries: TokenStream = gen_to_entries(fields);
+    let func_apply_env: TokenStream = gen_apply_env(fields);
 
     quote! {
         impl #name {
             #func_parse_string
-            #func_parse_map
+            #func_merge_map
             #func_default
             #func_to_string
+            #func_to_entries
+            #func_apply_env
         }
     }.into()
 }
 
+/// **for enums of unit variants only**
+/// - implements `ConfigValue` so the enum can be used as type of a setting,
+///   variants are written in snake case and parsed case insensitive
+/// - the variants are listed in the default configuration
+#[proc_macro_derive(ConfigEnum)]
+pub fn derive_enum(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let name = &ast.ident;
+    let variants: Vec<syn::Ident> = if let syn::Data::Enum(syn::DataEnum{ ref variants, .. }) = ast.data {
+        variants.iter()
+            .map(|variant| match variant.fields {
+                syn::Fields::Unit => variant.ident.clone(),
+                _ => panic!("the macro `ConfigEnum` applies only to enums of unit variants!"),
+            })
+            .collect()
+    } else {
+        panic!("the macro `ConfigEnum` applies only to enums!");
+    };
+    gen_config_enum(name, &variants).into()
+}
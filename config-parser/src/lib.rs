@@ -1,3 +1,3 @@
 
-pub use config_parser_common::{common::*, format::*};
-pub use config_parser_macro::ConfigParser;
+pub use config_parser_common::{common::*, format::*, value::*};
+pub use config_parser_macro::{ConfigEnum, ConfigParser};
@@ -0,0 +1,110 @@
+//! constraints on the values of settings (`range`, `one_of`, `non_empty`
+//! and `validate_with`)
+
+use config_parser::*;
+
+fn even(value: &u32) -> std::result::Result<(), String> {
+    if value.is_multiple_of(2) {
+        Ok(())
+    } else {
+        Err("an even number".to_owned())
+    }
+}
+
+#[derive(Debug, ConfigParser)]
+pub struct Limits {
+    /// (integer) number of entries
+    #[range(1..=1000)]
+    #[validate_with = "even"]
+    #[default_value(10u32)]
+    pub size: u32,
+
+    /// (string) when to style output
+    #[one_of("auto", "always", "never")]
+    #[default_value("auto")]
+    pub mode: String,
+
+    /// (string) separator between entries
+    #[non_empty]
+    #[default_value(" - ")]
+    pub separator: String,
+
+    /// (array) directories
+    #[non_empty]
+    #[default_value(vec!["/tmp".to_owned()])]
+    pub directories: Vec<String>,
+}
+
+#[derive(Debug, ConfigParser)]
+pub struct Config {
+    #[nested_config]
+    pub limits: Limits,
+}
+
+fn parse(input: &str) -> (Config, Vec<String>) {
+    let mut config = Config::default();
+    let messages = match config.parse_from_string(input) {
+        Ok(()) => Vec::new(),
+        Err(diagnostics) => diagnostics.iter().map(ToString::to_string).collect(),
+    };
+    (config, messages)
+}
+
+#[test]
+fn valid_values_are_accepted() {
+    let (config, messages) = parse("[limits]\nsize = 1000\nmode = \"never\"\nseparator = \">\"\ndirectories = [\"/a\"]\n");
+    assert_eq!(messages, Vec::<String>::new());
+    assert_eq!(config.limits.size, 1000);
+    assert_eq!(config.limits.mode, "never");
+}
+
+#[test]
+fn errors_name_key_and_allowed_values() {
+    let (config, messages) = parse("[limits]\nsize = 0\nmode = \"often\"\nseparator = \"\"\ndirectories = []\n");
+    assert_eq!(messages, vec![
+        "2:1: invalid value `0` for `limits.size`, expected a value in `1..=1000`".to_owned(),
+        "3:1: invalid value `often` for `limits.mode`, expected one of `auto`, `always`, `never`".to_owned(),
+        "4:1: invalid value `` for `limits.separator`, expected a non-empty value".to_owned(),
+        "5:1: invalid array for `limits.directories`, expected a non-empty value".to_owned(),
+    ]);
+    // invalid values keep the previous value
+    assert_eq!(config.limits.size, 10);
+    assert_eq!(config.limits.mode, "auto");
+    assert_eq!(config.limits.separator, " - ");
+    assert_eq!(config.limits.directories, vec!["/tmp".to_owned()]);
+}
+
+#[test]
+fn validate_with_describes_expected_values() {
+    let (config, messages) = parse("[limits]\nsize = 7\n");
+    assert_eq!(messages, vec!["2:1: invalid value `7` for `limits.size`, expected an even number".to_owned()]);
+    assert_eq!(config.limits.size, 10);
+
+    // the type is checked before the constraints
+    let (_, messages) = parse("[limits]\nsize = \"many\"\n");
+    assert_eq!(messages, vec!["2:1: invalid value `many` for `limits.size`, expected an integer".to_owned()]);
+}
+
+#[test]
+fn overrides_are_checked() {
+    let mut config = Config::default();
+    let mut keys = Vec::<String>::new();
+    let diagnostics = config.merge_from_map(parse_override("limits.size=2000").unwrap(), "", &mut keys);
+    assert_eq!(diagnostics.iter().map(ToString::to_string).collect::<Vec<String>>(), vec![
+        "invalid value `2000` for `limits.size`, expected a value in `1..=1000`".to_owned(),
+    ]);
+    assert!(keys.is_empty());
+}
+
+#[test]
+fn default_file_lists_constraints() {
+    let default = Config::default().to_string();
+    for line in [
+        "size = 10 # (integer) number of entries [range: 1..=1000] [checked by even]\n",
+        "mode = \"auto\" # (string) when to style output [possible values: auto, always, never]\n",
+        "separator = \" - \" # (string) separator between entries [non-empty]\n",
+        "directories = [\"/tmp\"] # (array) directories [non-empty]\n",
+    ] {
+        assert!(default.contains(line), "`{}` not found in\n{}", line.trim_end(), default);
+    }
+}
@@ -0,0 +1,180 @@
+//! fields of the types supported by `ConfigValue`, parsed through the
+//! code generated by `ConfigParser` and `ConfigEnum`
+
+use std::path::PathBuf;
+
+use config_parser::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, ConfigEnum)]
+pub enum DedupMode {
+    None,
+    Consecutive,
+    AllEntries,
+}
+
+#[derive(Debug, ConfigParser)]
+pub struct Settings {
+    /// (enum) how duplicates are removed
+    #[default_value(DedupMode::Consecutive)]
+    pub dedup_mode: DedupMode,
+
+    /// (integer) maximum size, unlimited if empty
+    #[default_value(Some(10u32))]
+    pub max_size: Option<u32>,
+
+    /// (array) ignored directories
+    #[default_value(vec!["/tmp".to_owned()])]
+    pub ignored: Vec<String>,
+
+    /// (path) directory of the data
+    #[default_value(PathBuf::from("/var/data"))]
+    pub directory: PathBuf,
+
+    /// (path) optional directory
+    #[default_value(Option::<PathBuf>::None)]
+    pub extra: Option<PathBuf>,
+
+    /// (string) name of the profile
+    #[default_value("default".to_owned())]
+    pub name: String,
+}
+
+#[derive(Debug, ConfigParser)]
+pub struct Config {
+    #[nested_config]
+    pub settings: Settings,
+}
+
+fn parse(input: &str) -> (Config, Vec<String>) {
+    let mut config = Config::default();
+    let messages = match config.parse_from_string(input) {
+        Ok(()) => Vec::new(),
+        Err(diagnostics) => diagnostics.iter().map(ToString::to_string).collect(),
+    };
+    (config, messages)
+}
+
+#[test]
+fn enum_variants_are_snake_case() {
+    let (config, messages) = parse("[settings]\ndedup_mode = \"all_entries\"\n");
+    assert_eq!(messages, Vec::<String>::new());
+    assert_eq!(config.settings.dedup_mode, DedupMode::AllEntries);
+
+    // parsed case insensitive
+    let (config, _) = parse("[settings]\ndedup_mode = \"NONE\"\n");
+    assert_eq!(config.settings.dedup_mode, DedupMode::None);
+}
+
+#[test]
+fn invalid_enum_value_lists_variants() {
+    let (config, messages) = parse("[settings]\ndedup_mode = \"all\"\n");
+    assert_eq!(messages, vec![
+        "2:1: invalid value `all` for `settings.dedup_mode`, expected one of `none`, `consecutive`, `all_entries`".to_owned(),
+    ]);
+    assert_eq!(config.settings.dedup_mode, DedupMode::Consecutive);
+}
+
+#[test]
+fn default_file_lists_enum_variants() {
+    let default = Config::default().to_string();
+    assert!(default.contains(
+        "dedup_mode = \"consecutive\" # (enum) how duplicates are removed [possible values: none, consecutive, all_entries]\n"
+    ), "{}", default);
+    assert!(default.contains("ignored = [\"/tmp\"] #"), "{}", default);
+    assert!(default.contains("extra = \"\" #"), "{}", default);
+}
+
+#[test]
+fn options_are_unset_by_empty_string_or_none() {
+    let (config, messages) = parse("[settings]\nmax_size = 20\n");
+    assert_eq!(messages, Vec::<String>::new());
+    assert_eq!(config.settings.max_size, Some(20));
+
+    let (config, _) = parse("[settings]\nmax_size = \"\"\n");
+    assert_eq!(config.settings.max_size, None);
+    let (config, _) = parse("[settings]\nmax_size = \"none\"\n");
+    assert_eq!(config.settings.max_size, None);
+
+    let (config, messages) = parse("[settings]\nmax_size = \"big\"\n");
+    assert_eq!(messages, vec![
+        "2:1: invalid value `big` for `settings.max_size`, expected an integer or an empty string".to_owned(),
+    ]);
+    assert_eq!(config.settings.max_size, Some(10));
+}
+
+#[test]
+fn arrays_are_parsed_from_toml_arrays_and_overrides() {
+    let (config, messages) = parse("[settings]\nignored = [\"/proc\", '/sys']\n");
+    assert_eq!(messages, Vec::<String>::new());
+    assert_eq!(config.settings.ignored, vec!["/proc".to_owned(), "/sys".to_owned()]);
+
+    let (config, _) = parse("[settings]\nignored = []\n");
+    assert!(config.settings.ignored.is_empty());
+
+    // overrides and environment variables are separated by commas
+    let mut config = Config::default();
+    let mut keys = Vec::<String>::new();
+    let diagnostics = config.merge_from_map(parse_override("settings.ignored=/a, /b").unwrap(), "", &mut keys);
+    assert!(diagnostics.is_empty());
+    assert_eq!(config.settings.ignored, vec!["/a".to_owned(), "/b".to_owned()]);
+    assert_eq!(keys, vec!["settings.ignored".to_owned()]);
+}
+
+#[test]
+fn array_elements_are_checked() {
+    let (_, messages) = parse("[settings]\nignored = \"/proc\"\ndedup_mode = [\"none\"]\n");
+    // a single string is one element, like an override
+    assert_eq!(messages, vec![
+        "3:1: invalid array for `settings.dedup_mode`, expected one of `none`, `consecutive`, `all_entries`".to_owned(),
+    ]);
+
+    // numbers are not strings
+    let (config, messages) = parse("[settings]\nignored = [1, 2]\n");
+    assert_eq!(messages, vec![
+        "2:1: invalid array for `settings.ignored`, expected an array of a string".to_owned(),
+    ]);
+    assert_eq!(config.settings.ignored, vec!["/tmp".to_owned()]);
+
+    let (_, messages) = parse("[settings]\nignored = [[\"/a\"], {b = 1}]\n");
+    assert_eq!(messages, vec![
+        "2:1: invalid array for `settings.ignored`, expected an array of a string".to_owned(),
+    ]);
+}
+
+#[test]
+fn strings_reject_other_values() {
+    let (config, messages) = parse("[settings]\nname = 1\n");
+    assert_eq!(messages, vec!["2:1: invalid value `1` for `settings.name`, expected a string".to_owned()]);
+    assert_eq!(config.settings.name, "default");
+
+    let (_, messages) = parse("[settings]\nname = true\ndirectory = 2024-01-01\n");
+    assert_eq!(messages, vec![
+        "2:1: invalid value `true` for `settings.name`, expected a string".to_owned(),
+        "3:1: invalid value `2024-01-01` for `settings.directory`, expected a path".to_owned(),
+    ]);
+
+    // overrides and environment variables are not typed, their values are text
+    let mut config = Config::default();
+    let mut keys = Vec::<String>::new();
+    let diagnostics = config.merge_from_map(parse_override("settings.name=1").unwrap(), "", &mut keys);
+    assert!(diagnostics.is_empty());
+    assert_eq!(config.settings.name, "1");
+}
+
+#[test]
+fn paths_expand_tilde() {
+    // the only test reading `HOME`
+    std::env::set_var("HOME", "/home/tester");
+    let (config, messages) = parse("[settings]\ndirectory = \"~/data\"\nextra = \"~\"\n");
+    assert_eq!(messages, Vec::<String>::new());
+    assert_eq!(config.settings.directory, PathBuf::from("/home/tester/data"));
+    assert_eq!(config.settings.extra, Some(PathBuf::from("/home/tester")));
+
+    // only a leading `~` (followed by `/`) is expanded
+    let (config, _) = parse("[settings]\ndirectory = \"~tester/data\"\nextra = \"/srv/~\"\n");
+    assert_eq!(config.settings.directory, PathBuf::from("~tester/data"));
+    assert_eq!(config.settings.extra, Some(PathBuf::from("/srv/~")));
+
+    let (config, _) = parse("[settings]\nextra = \"\"\n");
+    assert_eq!(config.settings.extra, None);
+}
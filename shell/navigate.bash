@@ -0,0 +1,78 @@
+# integration of `navigate` for bash, printed by `navigate init bash`
+
+function __call_navigate {
+    # machine readable output (`--format`, `--print`) is printed, not evaluated
+    if [[ " $* " == *" --format"* || " $* " == *" --print "* ]]; then
+        navigate --pid $$ --shell bash "$@"
+        return
+    fi
+    # messages are printed to stderr, stdout only carries the commands
+    local commands status
+    # stdout is captured, navigate cannot tell whether the output is displayed on a terminal
+    local tty=()
+    [[ -t 1 ]] && tty=(--tty)
+    commands="$(navigate --pid $$ --shell bash "${tty[@]}" "$@")"
+    status=$?
+    eval "$commands"
+    # the directory change is recorded already (prompt hook of `init --hook`)
+    __navigate_last_pwd="$PWD"
+    return $status
+}
+
+function {{push}} {
+    __call_navigate push "$@"
+}
+
+function {{pop}} {
+    __call_navigate pop "$@"
+}
+
+function {{stack}} {
+    __call_navigate stack "$@"
+}
+
+function {{book}} {
+    __call_navigate bookmark "$@"
+}
+
+function {{navconfig}} {
+    __call_navigate configuration "$@"
+}
+
+# completions generated from the arguments of `navigate`
+{{completions}}
+
+# completion function for `{{push}}`, adds stack entries `=<n>`
+function _navigate_push {
+    _{{push}} "$@"
+    local current="${COMP_WORDS[COMP_CWORD]}"
+    if [[ "${current}" == =* ]]; then
+        COMPREPLY+=($(compgen -W "$(__call_navigate stack completions)" -- "${current}"))
+    fi
+}
+
+# completion function for `{{pop}}`, adds the number of entries to pop to reach a stack entry
+function _navigate_pop {
+    _{{pop}} "$@"
+    if [[ COMP_CWORD -eq 1 ]]; then
+        local entry counts=""
+        # popping <n> + 1 entries moves to the path of entry `=<n>`
+        for entry in $(__call_navigate stack completions); do
+            counts+=" $(( ${entry#=} + 1 ))"
+        done
+        COMPREPLY+=($(compgen -W "${counts}" -- "${COMP_WORDS[COMP_CWORD]}"))
+    fi
+}
+
+# completion function for `{{book}}`, adds bookmark names
+function _navigate_book {
+    _{{book}} "$@"
+    if [[ COMP_CWORD -eq 1 || "${COMP_WORDS[1]}" = "remove" || ( "${COMP_WORDS[1]}" = "rename" && COMP_CWORD -eq 2 ) ]]; then
+        COMPREPLY+=($(compgen -W "$(__call_navigate bookmark completions)" -- "${COMP_WORDS[COMP_CWORD]}"))
+    fi
+}
+
+complete -F _navigate_push -o bashdefault -o dirnames {{push}}
+complete -F _navigate_pop {{pop}}
+complete -F _navigate_book -o bashdefault -o default {{book}}
+complete -F _{{navconfig}} {{navconfig}}
@@ -0,0 +1,72 @@
+# integration of `navigate` for fish, printed by `navigate init fish`
+
+function __call_navigate
+    # machine readable output (`--format`, `--print`) is printed, not evaluated
+    if string match -q -- '--format*' $argv; or contains -- --print $argv
+        navigate --pid $fish_pid --shell fish $argv
+        return
+    end
+    # messages are printed to stderr, stdout only carries the commands
+    # the directory change is recorded already (`PWD` hook of `init --hook`)
+    set -g __navigate_active 1
+    # stdout is piped, navigate cannot tell whether the output is displayed on a terminal
+    set -l tty
+    isatty stdout; and set tty --tty
+    navigate --pid $fish_pid --shell fish $tty $argv | source
+    set -l navigate_status $pipestatus[1]
+    set -e __navigate_active
+    return $navigate_status
+end
+
+function {{push}}
+    __call_navigate push $argv
+end
+
+function {{pop}}
+    __call_navigate pop $argv
+end
+
+function {{stack}}
+    __call_navigate stack $argv
+end
+
+function {{book}}
+    __call_navigate bookmark $argv
+end
+
+function {{navconfig}}
+    __call_navigate configuration $argv
+end
+
+# stack entries as `=<n>\t<path>`
+function __navigate_stack_entries
+    __call_navigate stack completions --describe
+end
+
+# bookmarks as `<name>\t<path>`
+function __navigate_bookmarks
+    __call_navigate bookmark completions --describe
+end
+
+# settings as `<table>.<key>\t<value>`
+function __navigate_config_keys
+    __call_navigate configuration completions --describe
+end
+
+# popping <n> + 1 entries moves to the path of entry `=<n>`
+function __navigate_pop_counts
+    for entry in (__navigate_stack_entries)
+        set -l parts (string split -m 1 \t -- $entry)
+        echo -e (math (string sub -s 2 -- $parts[1]) + 1)"\t"$parts[2]
+    end
+end
+
+# completions generated from the arguments of `navigate`
+{{completions}}
+
+# values which change at runtime
+complete -c {{push}} -a "(__navigate_stack_entries)"
+complete -c {{pop}} -n "__fish_{{pop}}_needs_command" -f -a "(__navigate_pop_counts)"
+complete -c {{book}} -n "__fish_{{book}}_needs_command" -f -a "(__navigate_bookmarks)"
+complete -c {{book}} -n "__fish_{{book}}_using_subcommand remove rename" -f -a "(__navigate_bookmarks)"
+complete -c {{navconfig}} -n "__fish_{{navconfig}}_using_subcommand get set" -f -a "(__navigate_config_keys)"
@@ -1,15 +1,40 @@
 #![allow(dead_code)]
 #![allow(non_camel_case_types)]
 
-use clap::{Args, Parser, Subcommand};
+use clap::builder::{PossibleValue, PossibleValuesParser};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use super::config::Config;
+use super::shell::Shell;
 
 /// implements stack for cd wrapper script
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about=None)]
 pub struct Arguments {
-    /// process id of parent shell
+    /// process id of parent shell (defaults to the parent process of navigate)
     #[arg(short, long)]
-    pub pid: u32,
+    pub pid: Option<u32>,
+
+    /// shell evaluating the output
+    #[arg(long, value_enum, default_value_t)]
+    pub shell: Shell,
+
+    /// when to style output, overrides `format.color` of the configuration
+    #[arg(long, global = true, value_parser = ["auto", "always", "never"])]
+    pub color: Option<String>,
+
+    /// configuration file used instead of `$XDG_CONFIG_HOME/navigate/navigate.toml`
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// override a setting, e.g. `-o general.show_stack_on_push=true` (repeatable)
+    #[arg(short = 'o', long = "option", global = true, value_name = "KEY=VALUE")]
+    pub overrides: Vec<String>,
+
+    /// the calling shell displays the output on a terminal (passed by the shell integration)
+    #[arg(long, hide = true)]
+    pub tty: bool,
 
     /// subcommand
     #[command(subcommand)]
@@ -30,8 +55,17 @@ pub enum Action {
     /// navigate to bookmark and add current path to the stack
     bookmark(BookmarkArgs),
 
-    /// display current configuartion (mostly for debugging)
-    configuration,
+    /// show, check and change the configuration
+    configuration(ConfigArgs),
+
+    /// print the integration script for a shell, e.g. `eval "$(navigate init bash)"`
+    init(InitArgs),
+
+    /// print the absolute path of a path, stack entry `=<n>` or bookmark, e.g. `cd "$(navigate resolve api)"`
+    resolve(ResolveArgs),
+
+    /// push <previous> if the directory was changed without navigate (called by the prompt hook of `init --hook`)
+    observe(ObserveArgs),
 }
 
 #[derive(Debug, Clone, Args)]
@@ -40,6 +74,10 @@ pub struct PushArgs {
     #[arg(short, long)]
     pub show_stack: Option<bool>,
 
+    /// print the path instead of changing to it (for scripts, without the shell integration)
+    #[arg(long)]
+    pub print: bool,
+
     /// change to <path>
     #[arg(num_args = 0..)]
     pub path: Option<Vec<String>>,
@@ -71,6 +109,10 @@ pub struct StackArgs {
     #[arg(short = 'n', long)]
     pub hide_numbers: Option<bool>,
 
+    /// print the stack as machine readable records
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
+
     /// stack subcommand
     #[command(subcommand)]
     pub stack_action: Option<StackAction>,
@@ -80,6 +122,17 @@ pub struct StackArgs {
 pub enum StackAction {
     /// clear stack
     clear,
+
+    /// get stack entries for shell completions
+    #[command(hide = true)]
+    completions(CompletionArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct CompletionArgs {
+    /// print one `<value>:<description>` pair per line (zsh style)
+    #[arg(short, long)]
+    pub describe: bool,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -94,35 +147,221 @@ pub struct BookmarkArgs {
 
 #[derive(Debug, Clone, Subcommand)]
 pub enum BookmarkAction {
-    /// list all bookmarks
-    list,
+    /// list all bookmarks active on this host
+    list(BookmarkListArgs),
 
-    /// add a bookmark with `book add <name> <path>`
+    /// add a bookmark with `book add <name> <path> [--fallback <path>]...`
     add(BookmarkSubArgs),
 
-    /// remove a bookmark by name `book remove <name>`
-    remove(BookmarkSubArgs),
+    /// remove bookmarks by name or glob pattern `book remove <name|pattern>...`
+    remove(BookmarkRemoveArgs),
 
     /// remove bookmarks with invalid paths
-    clean,
+    clean(BookmarkCleanArgs),
+
+    /// rename a bookmark with `book rename <name> <new-name>`
+    rename(BookmarkRenameArgs),
+
+    /// show the recent changes to the bookmarks
+    history(BookmarkHistoryArgs),
+
+    /// revert the last <count> changes to the bookmarks
+    undo(BookmarkHistoryArgs),
 
     /// get bookmarknames for shell completions
-    completions,
+    #[command(hide = true)]
+    completions(CompletionArgs),
 }
 
 #[derive(Debug, Clone, Args)]
 pub struct BookmarkSubArgs {
-    /// name of bookmark to add/remove
+    /// name of bookmark to add
     pub name: String,
 
+    /// add the bookmark to the section of the hosts matching <HOST> (glob pattern)
+    #[arg(long)]
+    pub host: Option<String>,
+
+    /// fallback path used if <PATH> does not exist (repeat for more candidates)
+    #[arg(short, long)]
+    pub fallback: Vec<String>,
+
     /// path of bookmark to add
     #[arg(num_args = 0..)]
     pub path: Vec<String>,
 }
 
+#[derive(Debug, Clone, Args)]
+pub struct BookmarkListArgs {
+    /// show the bookmarks of all hosts
+    #[arg(short, long)]
+    pub all_hosts: bool,
+
+    /// print the bookmarks as machine readable records
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct BookmarkRemoveArgs {
+    /// names or glob patterns (`*`, `?`) of bookmarks to remove
+    #[arg(num_args = 1.., required = true)]
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct BookmarkCleanArgs {
+    /// only show the bookmarks which would be removed
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// do not ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// also remove bookmarks on file systems which are currently not mounted
+    #[arg(long)]
+    pub include_offline: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct BookmarkRenameArgs {
+    /// current name of the bookmark
+    pub name: String,
+
+    /// new name of the bookmark
+    pub new_name: String,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct BookmarkHistoryArgs {
+    /// number of changes to show/undo
+    pub count: Option<usize>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ResolveArgs {
+    /// path, stack entry `=<n>` or bookmark name
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ObserveArgs {
+    /// directory the shell was in before the change
+    pub previous: String,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct InitArgs {
+    /// shell to print the integration script for
+    #[arg(value_enum)]
+    pub shell: Shell,
+
+    /// prefix prepended to the names of all functions (e.g. `n` for `npush`, `npop`, ..)
+    #[arg(long, default_value = "")]
+    pub prefix: String,
+
+    /// name of the function to push a path
+    #[arg(long, default_value = "push")]
+    pub push: String,
+
+    /// name of the function to pop entries
+    #[arg(long, default_value = "pop")]
+    pub pop: String,
+
+    /// name of the function to show the stack
+    #[arg(long, default_value = "stack")]
+    pub stack: String,
+
+    /// name of the function to manage and jump to bookmarks
+    #[arg(long, default_value = "book")]
+    pub book: String,
+
+    /// name of the function to show the configuration
+    #[arg(long, default_value = "navconfig")]
+    pub navconfig: String,
+
+    /// override `cd` to push the current path onto the stack
+    #[arg(long)]
+    pub cd: bool,
+
+    /// install a prompt hook which pushes directories left without navigate (e.g. with `cd` or `z`)
+    #[arg(long)]
+    pub hook: bool,
+}
+
 #[derive(Debug, Clone, Args)]
 pub struct ConfigArgs {
-    /// convert styles to ansi escape sequences
+    /// configuration subcommand, lists the settings if omitted
+    #[command(subcommand)]
+    pub action: Option<ConfigAction>,
+
+    /// print the settings as machine readable records
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum ConfigAction {
+    /// print the value of a setting
+    get(ConfigGetArgs),
+
+    /// change a setting in the user configuration file (or the file of `--config`)
+    set(ConfigSetArgs),
+
+    /// open the user configuration file with `$VISUAL` or `$EDITOR` and check it afterwards
+    edit,
+
+    /// check the configuration for syntax errors, unknown settings and invalid values
+    check,
+
+    /// print the path of the user configuration file
+    path(ConfigPathArgs),
+
+    /// print the default configuration file
+    defaults,
+
+    /// get the settings for shell completions
+    #[command(hide = true)]
+    completions(CompletionArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ConfigGetArgs {
+    /// key of the setting, `<table>.<key>`
+    #[arg(value_parser = config_keys(), hide_possible_values = true)]
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ConfigSetArgs {
+    /// key of the setting, `<table>.<key>`
+    #[arg(value_parser = config_keys(), hide_possible_values = true)]
+    pub key: String,
+
+    /// new value, written as it would be in the file without quotes, e.g. ` > ` or `true`
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ConfigPathArgs {
+    /// print all configuration files which are read, in the order they are applied
     #[arg(short, long)]
-    pub convert: Option<bool>,
+    pub all: bool,
+}
+
+/// keys of all settings, used to validate and complete keys
+fn config_keys() -> PossibleValuesParser {
+    PossibleValuesParser::new(Config::default().to_entries().into_iter().map(|(key, _)| PossibleValue::new(key)))
+}
+
+/// machine readable output, printed as it is instead of being evaluated by the shell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// array of json objects
+    json,
+    /// one record per line, fields separated by tabs
+    tsv,
+    /// records terminated by nul bytes, fields separated by tabs
+    null,
 }
@@ -4,16 +4,139 @@ use std::collections::BTreeMap;
 use std::fs;
 use std::fs::File;
 use std::io::{Error, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use dirs::{config_dir, home_dir};
+use serde_json::Value;
+use sysinfo::System;
 
-use super::{config::*, util::to_rooted};
+use super::{config::*, journal::*, mounts::is_offline, records::Record, util::{expand_tilde, format_timestamp, glob_match, is_glob, to_rooted}};
 use config_parser::{apply_format, make_padding_string};
 
+/// a bookmark holds an ordered list of candidate paths, the first
+/// existing one is used (e.g. `notes=/data/notes|~/notes`)
+/// the paths are kept as written, so `~` works on every host sharing the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub paths: Vec<PathBuf>,
+}
+
+impl Bookmark {
+    /// separates the candidate paths in the bookmarks file, written as `\|`
+    /// inside a path (and `\` as `\\`)
+    const PATH_SEPARATOR: char = '|';
+    const ESCAPE: char = '\\';
+
+    /// parses the value of a line in the bookmarks file
+    pub fn from_file_string(value: &str) -> Result<Self> {
+        let mut candidates = vec![String::new()];
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            let current = candidates.last_mut().unwrap();
+            match c {
+                Self::ESCAPE => match chars.next() {
+                    Some(next) if next == Self::PATH_SEPARATOR || next == Self::ESCAPE => current.push(next),
+                    Some(next) => current.extend([c, next]),
+                    None => current.push(c),
+                },
+                Self::PATH_SEPARATOR => candidates.push(String::new()),
+                _ => current.push(c),
+            }
+        }
+        let mut paths = Vec::<PathBuf>::new();
+        for candidate in candidates.iter().map(|entry| entry.trim()).filter(|entry| !entry.is_empty()) {
+            match PathBuf::from_str(candidate) {
+                Ok(value) => paths.push(value),
+                Err(err) => return Err(Error::other(err.to_string())),
+            };
+        }
+        Ok(Bookmark { paths })
+    }
+
+    /// roots a path given on the command line, paths starting with `~`
+    /// are kept to be expanded on use
+    pub fn normalize_path(path: &mut PathBuf) -> Result<()> {
+        if path.starts_with("~") {
+            return Ok(());
+        }
+        to_rooted(path)
+    }
+
+    /// returns the candidates with `~` expanded to the home directory
+    pub fn resolved_paths(&self) -> Vec<PathBuf> {
+        self.paths.iter().map(|path| expand_tilde(path)).collect()
+    }
+
+    /// returns the value as written to the bookmarks file
+    pub fn to_file_string(&self) -> String {
+        self.paths.iter().map(|path| Self::escape(&path_to_string(path))).collect::<Vec<String>>().join(&Self::PATH_SEPARATOR.to_string())
+    }
+
+    /// escapes the separator in a candidate path, see `PATH_SEPARATOR`
+    pub fn escape(path: &str) -> String {
+        path.replace(Self::ESCAPE, "\\\\").replace(Self::PATH_SEPARATOR, "\\|")
+    }
+
+    /// joins candidate paths as in the listing, e.g. `/data/notes | ~/notes`
+    pub fn join_candidates(paths: &[String]) -> String {
+        paths.join(&format!(" {} ", Self::PATH_SEPARATOR))
+    }
+
+    /// returns the first candidate pointing to a directory
+    pub fn active_path(&self) -> Option<PathBuf> {
+        self.resolved_paths().into_iter().find(|path| path.is_dir())
+    }
+
+    /// a bookmark is valid as long as one of its candidates exists
+    pub fn is_valid(&self) -> bool {
+        self.active_path().is_some()
+    }
+
+    /// valid if a candidate exists, offline if a candidate lies on a
+    /// file system which is not mounted and missing otherwise
+    pub fn state(&self) -> BookmarkState {
+        if self.is_valid() {
+            BookmarkState::Valid
+        } else if self.resolved_paths().iter().any(|path| is_offline(path)) {
+            BookmarkState::Offline
+        } else {
+            BookmarkState::Missing
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookmarkState {
+    Valid,
+    /// path is on a removable or network file system which is currently not mounted
+    Offline,
+    Missing,
+}
+
+impl BookmarkState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookmarkState::Valid => "valid",
+            BookmarkState::Offline => "offline",
+            BookmarkState::Missing => "missing",
+        }
+    }
+}
+
+/// bookmarks of one section of the bookmarks file, `host` is `None` for
+/// the common section and the hostname glob pattern of `[<pattern>]` otherwise
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub host: Option<String>,
+    pub bookmarks: BTreeMap<String, Bookmark>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Bookmarks {
-    bookmarks: BTreeMap<String, PathBuf>,
+    /// common section first, then the host sections in file order
+    sections: Vec<Section>,
+    hostname: String,
+    journal: Journal,
 }
 
 impl Bookmarks {
@@ -22,7 +145,9 @@ impl Bookmarks {
     /// generates and populates a new instance of Config
     pub fn new() -> Result<Self> {
         let mut bookmarks = Bookmarks {
-            bookmarks: BTreeMap::<String, PathBuf>::new(),
+            sections: vec![Section { host: None, bookmarks: BTreeMap::<String, Bookmark>::new() }],
+            hostname: System::host_name().unwrap_or_default(),
+            journal: Journal::new()?,
         };
         // get home directory path
         let mut bookmark_file = match config_dir() {
@@ -39,30 +164,35 @@ impl Bookmarks {
 
         let bookmarks_str = fs::read_to_string(bookmark_file)?;
         for entry in bookmarks_str.lines() {
-            let tokens: Vec<&str> = entry.split("=").collect();
+            let entry = entry.trim();
+            // `[<hostname-pattern>]` starts a host specific section
+            if entry.starts_with('[') && entry.ends_with(']') {
+                let host = entry[1..entry.len() - 1].trim().to_string();
+                bookmarks.sections.push(Section { host: Some(host), bookmarks: BTreeMap::new() });
+                continue;
+            }
+            let tokens: Vec<&str> = entry.splitn(2, "=").collect();
             if tokens.len() != 2 {
                 continue;
             }
             let key: String = String::from(tokens[0]);
-            let mut path: PathBuf = match PathBuf::from_str(tokens[1]) {
-                Ok(value) => value,
-                Err(err) => return Err(Error::other(err.to_string())),
-            };
-            to_rooted(&mut path)?;
-
-            // if !path.is_dir() {
-            //     continue;
-            // }
+            let bookmark = Bookmark::from_file_string(tokens[1])?;
 
-            bookmarks.bookmarks.insert(key, path);
+            if let Some(section) = bookmarks.sections.last_mut() {
+                section.bookmarks.insert(key, bookmark);
+            }
         }
         Ok(bookmarks)
     }
 
-    /// returns path of bookmark if it exists
+    /// returns the first existing path of bookmark if it exists
     pub fn get_path_by_name(&mut self, name: &str) -> Result<PathBuf> {
-        match self.bookmarks.get(name) {
-            Some(value) => Ok(value.to_owned()),
+        match self.active_bookmarks().get(name) {
+            Some(bookmark) => match bookmark.active_path() {
+                Some(path) => Ok(path),
+                None if bookmark.state() == BookmarkState::Offline => Err(Error::other(format!("-- bookmark `{}` is offline, its file system is not mounted", name))),
+                None => Err(Error::other(format!("-- none of the paths of bookmark `{}` exist", name))),
+            },
             None => Err(Error::other(format!(
                 "-- bookmark with name `{}` does not exist",
                 name
@@ -71,86 +201,315 @@ impl Bookmarks {
     }
 
     /// adds a key/value pair to bookmarks and writes the bookmarks file
-    pub fn add_bookmark(&mut self, name: &String, path: &PathBuf) -> Result<()> {
-        let mut path = path.to_path_buf();
-        to_rooted(&mut path)?;
-        if self.bookmarks.contains_key(name) {
+    /// `paths` are the candidates in order of preference, at least one of them has to exist
+    /// the bookmark is added to the section of `host` if provided and
+    /// to the common bookmarks otherwise
+    pub fn add_bookmark(&mut self, name: &String, paths: &[PathBuf], host: Option<&String>) -> Result<()> {
+        let mut bookmark = Bookmark { paths: paths.to_vec() };
+        for path in bookmark.paths.iter_mut() {
+            Bookmark::normalize_path(path)?;
+        }
+        let index = match host {
+            Some(host) => match self.sections.iter().position(|section| section.host.as_ref() == Some(host)) {
+                Some(index) => index,
+                None => {
+                    self.sections.push(Section { host: Some(host.to_string()), bookmarks: BTreeMap::new() });
+                    self.sections.len() - 1
+                }
+            },
+            None => 0,
+        };
+        if self.sections[index].bookmarks.contains_key(name) {
             return Err(Error::other(format!("-- bookmark with name `{name}` already exists")));
         }
-        if !path.is_dir() {
+        if !bookmark.is_valid() {
             return Err(Error::other(
-                "-- provided path argument does not point to a valid directory",
+                "-- none of the provided path arguments point to a valid directory",
             ));
         } else {
-            self.bookmarks.insert(name.to_string(), path.to_path_buf());
+            let value = bookmark.to_file_string();
+            self.sections[index].bookmarks.insert(name.to_string(), bookmark);
             self.write_bookmark_file()?;
+            self.journal.record(JournalAction::Add, vec![Change {
+                host: host.cloned(),
+                before: None,
+                after: Some((name.to_string(), value)),
+            }])?;
         }
         Ok(())
     }
 
-    /// removes a the entry with key=name if it exists, then writes the bookmarks file
-    pub fn remove_bookmark(&mut self, name: &String) -> Result<PathBuf> {
-        let path: PathBuf;
-        if self.bookmarks.contains_key(name) {
-            path = match self.bookmarks.remove(name) {
-                Some(path) => path,
-                None => return Err(Error::other("-- those bastards, they lied to me!")),
+    /// renames the entry with key=name, then writes the bookmarks file
+    /// returns the renamed bookmark
+    pub fn rename_bookmark(&mut self, name: &String, new_name: &String) -> Result<Bookmark> {
+        let index = match self.active_section(name) {
+            Some(value) => value,
+            None => return Err(Error::other(format!("-- bookmark with name `{name}` does not exist"))),
+        };
+        let section = &mut self.sections[index];
+        if section.bookmarks.contains_key(new_name) {
+            return Err(Error::other(format!("-- bookmark with name `{new_name}` already exists")));
+        }
+        let bookmark = match section.bookmarks.remove(name) {
+            Some(value) => value,
+            None => return Err(Error::other(format!("-- bookmark with name `{name}` does not exist"))),
+        };
+        section.bookmarks.insert(new_name.to_string(), bookmark.clone());
+        let host = section.host.clone();
+        self.write_bookmark_file()?;
+        self.journal.record(JournalAction::Rename, vec![Change {
+            host,
+            before: Some((name.to_string(), bookmark.to_file_string())),
+            after: Some((new_name.to_string(), bookmark.to_file_string())),
+        }])?;
+        Ok(bookmark)
+    }
+
+    /// removes all entries matching one of `patterns` (names or glob patterns),
+    /// then writes the bookmarks file
+    /// returns the removed entries and the patterns which did not match any bookmark
+    pub fn remove_bookmarks(&mut self, patterns: &[String]) -> Result<(BTreeMap<String, Bookmark>, Vec<String>)> {
+        let active = self.active_bookmarks();
+        let mut names = Vec::<String>::new();
+        let mut unmatched = Vec::<String>::new();
+        for pattern in patterns {
+            let matches: Vec<String> = if is_glob(pattern) {
+                active.keys().filter(|name| glob_match(pattern, name)).cloned().collect()
+            } else {
+                active.keys().filter(|name| *name == pattern).cloned().collect()
             };
-            self.write_bookmark_file()?;
-        } else {
-            return Err(Error::other(
-                "-- bookmark requested to delete does not exist",
-            ));
+            if matches.is_empty() {
+                unmatched.push(pattern.to_owned());
+            }
+            names.extend(matches);
         }
-        Ok(path)
+        if names.is_empty() {
+            return Err(Error::other(format!("-- no bookmark matches `{}`", patterns.join("`, `"))));
+        }
+        let removed = self.remove_active(&names, JournalAction::Remove)?;
+        Ok((removed, unmatched))
+    }
+
+    /// returns all bookmarks where none of the paths point to a directory,
+    /// offline bookmarks are only included if `include_offline` is set
+    pub fn get_invalid_bookmarks(&self, include_offline: bool) -> BTreeMap<String, Bookmark> {
+        self.active_bookmarks().into_iter()
+            .filter(|(_, bookmark)| match bookmark.state() {
+                BookmarkState::Valid => false,
+                BookmarkState::Offline => include_offline,
+                BookmarkState::Missing => true,
+            })
+            .collect()
+    }
+
+    /// removes bookmarks where none of the paths point to a directory,
+    /// offline bookmarks are only removed if `include_offline` is set
+    /// returns the removed entries
+    pub fn remove_invalid_paths(&mut self, include_offline: bool) -> Result<BTreeMap<String, Bookmark>> {
+        let names: Vec<String> = self.get_invalid_bookmarks(include_offline).into_keys().collect();
+        if names.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+        self.remove_active(&names, JournalAction::Clean)
     }
 
-    /// 
-    pub fn remove_invalid_paths(&mut self) -> Result<()> {
-        self.bookmarks.retain(|_, path| path.is_dir());
+    /// reverts the last `count` operations recorded in the journal
+    /// returns the reverted operations, newest first
+    pub fn undo(&mut self, count: usize) -> Result<Vec<Operation>> {
+        let operations = self.journal.undoable(count);
+        if operations.is_empty() {
+            return Err(Error::other("-- there is nothing to undo"));
+        }
+
+        // revert on a copy so a conflict leaves the bookmarks untouched
+        let mut sections = self.sections.clone();
+        for operation in &operations {
+            for change in operation.changes.iter().rev() {
+                let index = match sections.iter().position(|section| section.host == change.host) {
+                    Some(index) => index,
+                    None => {
+                        sections.push(Section { host: change.host.clone(), bookmarks: BTreeMap::new() });
+                        sections.len() - 1
+                    }
+                };
+                let bookmarks = &mut sections[index].bookmarks;
+                if let Some((name, value)) = &change.after {
+                    match bookmarks.get(name) {
+                        Some(current) if current.to_file_string() == *value => _ = bookmarks.remove(name),
+                        _ => return Err(Error::other(format!("-- cannot undo `{}`, bookmark `{}` has been changed since", operation.action.as_str(), name))),
+                    }
+                }
+                if let Some((name, value)) = &change.before {
+                    if bookmarks.contains_key(name) {
+                        return Err(Error::other(format!("-- cannot undo `{}`, bookmark `{}` already exists", operation.action.as_str(), name)));
+                    }
+                    bookmarks.insert(name.clone(), Bookmark::from_file_string(value)?);
+                }
+            }
+        }
+        self.sections = sections;
         self.write_bookmark_file()?;
+        let ids: Vec<u64> = operations.iter().map(|operation| operation.id).collect();
+        self.journal.record_undo(&ids)?;
+        Ok(operations)
+    }
 
-        Ok(())
+    /// formats the last `count` operations of the journal to string, newest first
+    pub fn history_to_formatted_string(&self, count: usize, config: &Config) -> Result<String> {
+        let operations: Vec<&Operation> = self.journal.operations().iter()
+            .rev()
+            .filter(|operation| operation.action != JournalAction::Undo)
+            .take(count)
+            .collect();
+        if operations.is_empty() {
+            return Ok("-- the bookmark history is empty".to_owned());
+        }
+
+        let mut buffer = String::new();
+        let max_num_len: usize = operations.len().to_string().len();
+        for (n, operation) in operations.iter().enumerate() {
+            let padding: String = make_padding_string(max_num_len - (n + 1).to_string().len());
+            // undone operations are marked in plain text as well, styles may be disabled
+            let undone: &str = if operation.undone { " (undone)" } else { "" };
+            let mut line = format!("{}{}{}{} {}{}\n",
+                n + 1, padding, config.format.bookmarks_separator,
+                format_timestamp(operation.timestamp), operation.action.as_str(), undone);
+            for change in &operation.changes {
+                let entry = match (&change.before, &change.after) {
+                    (Some((old_name, _)), Some((new_name, _))) => format!("{} -> {}",
+                        apply_format(old_name, &config.styles.bookmarks_name_style),
+                        apply_format(new_name, &config.styles.bookmarks_name_style)),
+                    (Some((name, path)), None) | (None, Some((name, path))) => format!("{}{}{}",
+                        apply_format(name, &config.styles.bookmarks_name_style),
+                        apply_format(&config.format.bookmarks_separator, &config.styles.bookmarks_seperator_style),
+                        apply_format(path, &config.styles.bookmarks_path_style)),
+                    (None, None) => continue,
+                };
+                line.push_str(&format!("    {}\n", entry));
+            }
+            if operation.undone {
+                line = apply_format(&line, &config.styles.bookmarks_invalid_style);
+            }
+            buffer.push_str(&line);
+        }
+        Ok(buffer)
     }
 
-    /// formats and prints bookmarks to string
+    /// formats and prints the bookmarks active on this host to string
     pub fn to_formatted_string(&self, config: &Config) -> Result<String> {
+        let active = self.active_bookmarks();
+        if active.is_empty() {
+            return Ok("-- there are no bookmarks defined".to_owned());
+        }
+        Self::entries_to_formatted_string(&active, config)
+    }
+
+    /// formats and prints all sections of the bookmarks file to string,
+    /// including the sections of other hosts
+    pub fn all_hosts_to_formatted_string(&self, config: &Config) -> Result<String> {
         let mut buffer = String::new();
+        for section in &self.sections {
+            if let Some(host) = &section.host {
+                let state = if self.matches_host(host) { "" } else { " -- not active on this host" };
+                buffer.push_str(&format!("[{}]{}\n", apply_format(host, &config.styles.bookmarks_name_style), state));
+            }
+            buffer.push_str(&Self::entries_to_formatted_string(&section.bookmarks, config)?);
+        }
+        if buffer.is_empty() {
+            return Ok("-- there are no bookmarks defined".to_owned());
+        }
+        Ok(buffer)
+    }
 
-        if self.bookmarks.is_empty() {
-            buffer.push_str("-- there are no bookmarks defined");
+    /// returns the bookmarks active on this host (or all bookmarks if `all_hosts`) as
+    /// records with the fields `name`, `path`, `paths`, `valid`, `state` and `host`
+    pub fn to_records(&self, all_hosts: bool) -> Vec<Record> {
+        let mut entries = Vec::<(&String, &Bookmark, &Option<String>)>::new();
+        if all_hosts {
+            for section in &self.sections {
+                entries.extend(section.bookmarks.iter().map(|(name, bookmark)| (name, bookmark, &section.host)));
+            }
         } else {
-            let max_name_len =  match self.bookmarks.keys().map(String::len).max() {
+            // later sections override bookmarks of the same name
+            let mut active = BTreeMap::<&String, (&Bookmark, &Option<String>)>::new();
+            for section in self.active_sections() {
+                active.extend(section.bookmarks.iter().map(|(name, bookmark)| (name, (bookmark, &section.host))));
+            }
+            entries.extend(active.into_iter().map(|(name, (bookmark, host))| (name, bookmark, host)));
+        }
+
+        entries.into_iter().map(|(name, bookmark, host)| {
+            let paths = bookmark.resolved_paths();
+            let path = bookmark.active_path().or(paths.first().cloned()).map(|path| path_to_string(&path));
+            vec![
+                ("name", Value::from(name.clone())),
+                ("path", Value::from(path)),
+                ("paths", Value::from(paths.iter().map(|path| path_to_string(path)).collect::<Vec<String>>())),
+                ("valid", Value::from(bookmark.is_valid())),
+                ("state", Value::from(bookmark.state().as_str())),
+                ("host", Value::from(host.clone())),
+            ]
+        }).collect()
+    }
+
+    /// formats and prints a set of bookmark entries to string
+    /// (used for listing bookmarks as well as for reporting removed ones)
+    pub fn entries_to_formatted_string(entries: &BTreeMap<String, Bookmark>, config: &Config) -> Result<String> {
+        let mut buffer = String::new();
+
+        if !entries.is_empty() {
+            let max_name_len =  match entries.keys().map(String::len).max() {
                 Some(value) => value,
                 None => return Err(Error::other("-- failed to determine maximum bookmark name length")),
             };
-            for (raw_name, raw_path) in &self.bookmarks {
+            let home: Option<String> = if config.format.show_home_as_tilde {
+                match home_dir() {
+                    Some(value) => match value.into_os_string().into_string() {
+                        Ok(value) => Some(value),
+                        Err(error) => return Err(Error::other(format!("-- failed to conver home directory to string: {}", error.to_str().unwrap()))),
+                    },
+                    None => return Err(Error::other("-- `bookmarks_home_as_tilde` = true, but home directory can't be determined")),
+                }
+            } else {
+                None
+            };
+            for (raw_name, bookmark) in entries {
                 let padding: String = make_padding_string(max_name_len - raw_name.len());
                 let mut name: String = raw_name.clone();
                 let mut separator: String = config.format.bookmarks_separator.clone();
-                let mut path: String = raw_path.clone().into_os_string().into_string().unwrap();
-
-                if config.format.show_home_as_tilde {
-                    let home: String = match home_dir() {
-                        Some(value) => match value.into_os_string().into_string() {
-                            Ok(value) => value,
-                            Err(error) => return Err(Error::other(format!("-- failed to conver home directory to string: {}", error.to_str().unwrap()))),
-                        },
-                        None => return Err(Error::other("-- `bookmarks_home_as_tilde` = true, but home directory can't be determined")),
-                    };
-                    path = path.replace(&home, "~");
-                }
+                let active = bookmark.active_path();
+                let state = bookmark.state();
 
-                if raw_path.is_dir() {
-                    let slash: String = apply_format(&"/".to_owned(), &config.styles.bookmarks_punct_style)?;
-                    let mut segments: Vec<String> = path.split('/').map(|element| element.to_owned()).collect();
-                    for element in segments.iter_mut() {
-                        *element = apply_format(&element, &config.styles.bookmarks_path_style)?;
+                // print all candidates, the active one styled as path (and marked
+                // in plain text if there are several), the others as fallback
+                let resolved = bookmark.resolved_paths();
+                let mut candidates = Vec::<String>::new();
+                for raw_path in &resolved {
+                    let mut path: String = path_to_string(raw_path);
+                    if let Some(home) = &home {
+                        path = path.replace(home, "~");
+                    }
+                    path = Bookmark::escape(&path);
+                    if active.as_ref() == Some(raw_path) {
+                        let slash: String = apply_format("/", &config.styles.bookmarks_punct_style);
+                        let mut segments: Vec<String> = path.split('/').map(|element| element.to_owned()).collect();
+                        for element in segments.iter_mut() {
+                            *element = apply_format(element, &config.styles.bookmarks_path_style);
+                        }
+                        path = segments.join(&slash);
+                        if resolved.len() > 1 {
+                            path.push_str(" (active)");
+                        }
+                    } else if active.is_some() {
+                        path = apply_format(&path, &config.styles.bookmarks_fallback_style);
                     }
-                    path = segments.join(&slash);
+                    candidates.push(path);
+                }
+                let path: String = Bookmark::join_candidates(&candidates);
 
-                    name = apply_format(&name, &config.styles.bookmarks_name_style)?;
-                    separator = apply_format(&separator, &config.styles.bookmarks_seperator_style)?;
+                if active.is_some() {
+                    name = apply_format(&name, &config.styles.bookmarks_name_style);
+                    separator = apply_format(&separator, &config.styles.bookmarks_seperator_style);
                 }
 
                 let mut line: String;
@@ -159,8 +518,10 @@ impl Bookmarks {
                 } else {
                     line = format!("{}{}{}{}\n", name, separator, padding, path);
                 }
-                if !raw_path.is_dir() {
-                    line = apply_format(&line, &config.styles.bookmarks_invalid_style)?;
+                match state {
+                    BookmarkState::Valid => (),
+                    BookmarkState::Offline => line = apply_format(&line, &config.styles.bookmarks_offline_style),
+                    BookmarkState::Missing => line = apply_format(&line, &config.styles.bookmarks_invalid_style),
                 }
 
                 buffer.push_str(&line);
@@ -170,11 +531,12 @@ impl Bookmarks {
     }
 
     /// get bookmarknames as space separated values in one string (for shell completions)
+    /// offline bookmarks are kept, they become valid once their file system is mounted
     pub fn get_bookmark_names(&self) -> String {
-        let mut copy = self.bookmarks.clone();
+        let mut copy = self.active_bookmarks();
 
-        for (name, path) in copy.clone() {
-            if !path.is_dir() {
+        for (name, bookmark) in copy.clone() {
+            if bookmark.state() == BookmarkState::Missing {
                 _ = copy.remove(&name);
             }
         }
@@ -182,11 +544,88 @@ impl Bookmarks {
         names.join(" ")
     }
 
+    /// get bookmarknames with their paths (for shell completions with descriptions)
+    pub fn get_bookmark_descriptions(&self) -> Vec<(String, String)> {
+        self.active_bookmarks().iter()
+            .filter(|(_, bookmark)| bookmark.state() != BookmarkState::Missing)
+            .map(|(name, bookmark)| {
+                let path = match bookmark.active_path() {
+                    Some(path) => path_to_string(&path),
+                    None => bookmark.to_file_string(),
+                };
+                (name.clone(), path)
+            })
+            .collect()
+    }
+
+    /// returns the bookmarks active on this host, host sections
+    /// override common bookmarks of the same name
+    pub fn active_bookmarks(&self) -> BTreeMap<String, Bookmark> {
+        let mut active = BTreeMap::<String, Bookmark>::new();
+        for section in self.active_sections() {
+            active.extend(section.bookmarks.clone());
+        }
+        active
+    }
+
+    /// returns the sections which apply to this host
+    fn active_sections(&self) -> impl Iterator<Item = &Section> {
+        self.sections.iter().filter(|section| self.is_active(section))
+    }
+
+    /// returns the index of the section providing the active bookmark `name`
+    fn active_section(&self, name: &str) -> Option<usize> {
+        self.sections.iter()
+            .rposition(|section| self.is_active(section) && section.bookmarks.contains_key(name))
+    }
+
+    fn is_active(&self, section: &Section) -> bool {
+        match &section.host {
+            Some(host) => self.matches_host(host),
+            None => true,
+        }
+    }
+
+    fn matches_host(&self, pattern: &str) -> bool {
+        glob_match(&pattern.to_lowercase(), &self.hostname.to_lowercase())
+    }
+
+    /// removes the active bookmarks `names` from their sections, writes the
+    /// bookmarks file and records the removal in the journal
+    fn remove_active(&mut self, names: &[String], action: JournalAction) -> Result<BTreeMap<String, Bookmark>> {
+        let mut removed = BTreeMap::<String, Bookmark>::new();
+        let mut changes = Vec::<Change>::new();
+        for name in names {
+            let Some(index) = self.active_section(name) else {
+                continue;
+            };
+            if removed.contains_key(name) {
+                continue;
+            }
+            if let Some(bookmark) = self.sections[index].bookmarks.remove(name) {
+                changes.push(Change {
+                    host: self.sections[index].host.clone(),
+                    before: Some((name.clone(), bookmark.to_file_string())),
+                    after: None,
+                });
+                removed.insert(name.clone(), bookmark);
+            }
+        }
+        self.write_bookmark_file()?;
+        self.journal.record(action, changes)?;
+        Ok(removed)
+    }
+
     /// writes the bookmarks file
     fn write_bookmark_file(&self) -> Result<()> {
         let mut file_content = String::new();
-        for (mark, path) in self.bookmarks.iter() {
-            file_content.push_str(&format!("{}={}\n", mark, path.to_str().unwrap()));
+        for section in &self.sections {
+            if let Some(host) = &section.host {
+                file_content.push_str(&format!("\n[{}]\n", host));
+            }
+            for (mark, bookmark) in section.bookmarks.iter() {
+                file_content.push_str(&format!("{}={}\n", mark, bookmark.to_file_string()));
+            }
         }
 
         let path = match config_dir() {
@@ -201,3 +640,22 @@ impl Bookmarks {
         Ok(())
     }
 }
+
+fn path_to_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separator_in_paths_is_escaped() {
+        let bookmark = Bookmark { paths: vec![PathBuf::from("/data/a|b"), PathBuf::from("/data/c\\"), PathBuf::from("~/notes")] };
+        let value = bookmark.to_file_string();
+        assert_eq!(value, "/data/a\\|b|/data/c\\\\|~/notes");
+        assert_eq!(Bookmark::from_file_string(&value).unwrap(), bookmark);
+        // other backslashes are kept as written
+        assert_eq!(Bookmark::from_file_string("/data/x\\y").unwrap().paths, vec![PathBuf::from("/data/x\\y")]);
+    }
+}
@@ -4,12 +4,14 @@
 //! in said config file
 
 use dirs::config_dir;
+use std::collections::BTreeMap;
+use std::env::current_dir;
 use std::fs;
 use std::{
     io::{
         Error, Result
     },
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 use config_parser::*;
 
@@ -21,6 +23,10 @@ pub struct Config {
     pub format: FormatSettings,
     #[nested_config]
     pub styles: StyleSettings,
+    /// layer which set each setting (`<table>.<key>` -> layer), settings
+    /// not listed have their default value
+    #[no_config]
+    pub sources: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Default, ConfigParser)]
@@ -56,10 +62,18 @@ pub struct GeneralSettings {
     /// (bool) remove invalid bookmarks on call
     #[default_value(false)]
     pub cleanup_bookmarks: bool,
+
+    /// (array) glob patterns of directories the prompt hook (`observe`) does not push to the stack
+    #[default_value(Vec::<String>::new())]
+    pub observe_ignore_paths: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default, ConfigParser)]
 pub struct FormatSettings {
+    /// (enum) when to style output, 'auto' styles output displayed on a terminal
+    #[default_value(ColorMode::Auto)]
+    pub color: ColorMode,
+
     /// (bool) add padding before the separator if true, after if false
     #[default_value(true)]
     pub align_separators: bool,
@@ -73,93 +87,169 @@ pub struct FormatSettings {
     pub stack_hide_numbers: bool,
 
     /// (string) separator between stack numbers and paths
-    #[default_value("' - '")]
+    #[default_value(" - ")]
     pub stack_separator: String,
 
     /// (string) separator between bookmark names and paths
-    #[default_value("' - '")]
+    #[default_value(" - ")]
     pub bookmarks_separator: String,
 }
 
 #[derive(Debug, Clone, Default, ConfigParser)]
 pub struct StyleSettings {
-    /// (string) style applied to warnings
+    /// (style) style applied to warnings
     #[style_config]
-    #[default_value("'yellow, italic'")]
-    pub warning_style: String,
+    #[default_value("yellow, italic")]
+    pub warning_style: Style,
 
-    /// (string) style applied to errors
+    /// (style) style applied to errors
     #[style_config]
-    #[default_value("'red, bold'")]
-    pub error_style: String,
+    #[default_value("red, bold")]
+    pub error_style: Style,
 
-    /// (string) style applied to numbers when displaying the stack
+    /// (style) style applied to numbers when displaying the stack
     #[style_config]
-    #[default_value("'default'")]
-    pub stack_number_style: String,
+    #[default_value("default")]
+    pub stack_number_style: Style,
 
-    /// (string) style applied to separators when displaying the stack
+    /// (style) style applied to separators when displaying the stack
     #[style_config]
-    #[default_value("'cyan'")]
-    pub stack_separator_style: String,
+    #[default_value("cyan")]
+    pub stack_separator_style: Style,
 
-    /// (string) style applied to paths when displaying the stack
+    /// (style) style applied to paths when displaying the stack
     #[style_config]
-    #[default_value("'default'")]
-    pub stack_path_style: String,
+    #[default_value("default")]
+    pub stack_path_style: Style,
 
-    /// (string) style applied to punctuation (i.e. '/') when displaying the stack
+    /// (style) style applied to punctuation (i.e. '/') when displaying the stack
     #[style_config]
-    #[default_value("'magenta'")]
-    pub stack_punct_style: String,
+    #[default_value("magenta")]
+    pub stack_punct_style: Style,
 
-    /// (string) style applied to punctuation (i.e. '/') when displaying the stack
+    /// (style) style applied to punctuation (i.e. '/') when displaying the stack
     #[style_config]
-    #[default_value("'default, strikethrough'")]
-    pub stack_invalid_style: String,
+    #[default_value("default, strikethrough")]
+    pub stack_invalid_style: Style,
 
-    /// (string) style applied to bookmark names when displaying the bookmarks
+    /// (style) style applied to bookmark names when displaying the bookmarks
     #[style_config]
-    #[default_value("'default'")]
-    pub bookmarks_name_style: String,
+    #[default_value("default")]
+    pub bookmarks_name_style: Style,
 
-    /// (string) style applied to separators when displaying the bookmarks
+    /// (style) style applied to separators when displaying the bookmarks
     #[style_config]
-    #[default_value("'cyan'")]
-    pub bookmarks_seperator_style: String,
+    #[default_value("cyan")]
+    pub bookmarks_seperator_style: Style,
 
-    /// (string) style applied to paths when displaying the bookmarks
+    /// (style) style applied to paths when displaying the bookmarks
     #[style_config]
-    #[default_value("'default'")]
-    pub bookmarks_path_style: String,
+    #[default_value("default")]
+    pub bookmarks_path_style: Style,
 
-    /// (string) style applied to punctuation (i.e. '/') when displaying the bookmarks
+    /// (style) style applied to punctuation (i.e. '/') when displaying the bookmarks
     #[style_config]
-    #[default_value("'magenta'")]
-    pub bookmarks_punct_style: String,
+    #[default_value("magenta")]
+    pub bookmarks_punct_style: Style,
 
-    /// (string) style applied to punctuation (i.e. '/') when displaying the bookmarks
+    /// (style) style applied to invalid bookmarks when displaying the bookmarks
     #[style_config]
-    #[default_value("'strikethrough'")]
-    pub bookmarks_invalid_style: String,
+    #[default_value("strikethrough")]
+    pub bookmarks_invalid_style: Style,
+
+    /// (style) style applied to bookmarks on file systems which are not mounted
+    #[style_config]
+    #[default_value("dim, italic")]
+    pub bookmarks_offline_style: Style,
+
+    /// (style) style applied to inactive fallback paths when displaying the bookmarks
+    #[style_config]
+    #[default_value("dim")]
+    pub bookmarks_fallback_style: Style,
 }
 
 impl Config {
     const CONFIG_DIRECTORY_NAME: &str = "navigate";
     const CONFIG_FILE_NAME: &str = "navigate.toml";
     const DEFAULT_CONFIG_NAME: &str = "default.toml";
+    const SYSTEM_CONFIG_FILE: &str = "/etc/navigate/navigate.toml";
+    const PROJECT_CONFIG_NAME: &str = ".navigate.toml";
+    const ENVIRONMENT_PREFIX: &str = "NAVIGATE";
+    const USER_FILE_HEADER: &str = "# configuration file for `navigate`
+#
+# settings defined here override the system file `/etc/navigate/navigate.toml`,
+# all settings and their default values are listed in `default.toml`
+# e.g. `navconfig set general.dedup_stack true` or
+#
+# [general]
+# dedup_stack = true
+";
     const DEFAULT_FILE_HEADER: &str = "
 # default configuration file for `navigate`
 #
 # value type should be in the comment
-# string values have to be quoted, both single and double quotes work
+# the file is parsed as toml, string values have to be quoted
 # integer and boolean values do not need quotes
 # boolean values are either `true` or `false`
 ";
 
     /// generates and populates a new instance of Config
-    pub fn new() -> Result<Self> {
+    /// the configuration layers are applied in this order, later layers
+    /// override the settings they define:
+    /// - system file `/etc/navigate/navigate.toml`
+    /// - user file `$XDG_CONFIG_HOME/navigate/navigate.toml`
+    /// - project file `.navigate.toml` in the working directory or one of its parents
+    /// - environment variables `NAVIGATE_<TABLE>__<KEY>`, e.g. `NAVIGATE_GENERAL__DEDUP_STACK=true`
+    /// - `overrides` from the command line (`-o <table>.<key>=<value>`)
+    ///
+    /// `file` replaces the user file (`--config`)
+    ///
+    /// returns the problems found in the configuration files as
+    /// `<file>:<line>:<column>: <message>` (`environment: <message>` for the
+    /// environment variables), the affected settings keep their values
+    pub fn new(file: Option<&Path>, overrides: &[String]) -> Result<(Self, Vec<String>)> {
         let mut config: Config = Self::default();
+        let layers = Self::layer_files(file)?;
+
+        let mut diagnostics = Vec::<String>::new();
+        for layer in &layers {
+            let layer_str = fs::read_to_string(layer)?;
+            let source = layer.display().to_string();
+            diagnostics.extend(config.merge_layer(&layer_str, &source));
+        }
+
+        let (keys, env_diagnostics) = config.apply_env(Self::ENVIRONMENT_PREFIX);
+        for key in keys {
+            config.sources.insert(key, "environment".to_owned());
+        }
+        diagnostics.extend(env_diagnostics.iter().map(|diagnostic| format!("environment: {}", diagnostic)));
+
+        // overrides get the same validation as the files
+        for entry in overrides {
+            let mut keys = Vec::<String>::new();
+            let override_diagnostics = match parse_override(entry) {
+                Ok(map) => config.merge_from_map(map, "", &mut keys),
+                Err(diagnostic) => vec![diagnostic],
+            };
+            for key in keys {
+                config.sources.insert(key, "command line".to_owned());
+            }
+            diagnostics.extend(override_diagnostics.iter().map(|diagnostic| format!("command line: {}", diagnostic)));
+        }
+
+        Ok((config, diagnostics))
+    }
+
+    /// returns the user configuration file or `file` (`--config`)
+    /// the default file and an empty user file are created if they do not exist
+    pub fn user_file(file: Option<&Path>) -> Result<PathBuf> {
+        if let Some(file) = file {
+            if !file.is_file() {
+                return Err(Error::other(format!("-- configuration file `{}` not found", file.display())));
+            }
+            return Ok(file.to_path_buf());
+        }
+
         // get configuration directory
         let mut config_file: PathBuf = match config_dir() {
             Some(value) => value,
@@ -168,10 +258,8 @@ impl Config {
             }
         };
         config_file.push(Self::CONFIG_DIRECTORY_NAME);
-        if !config_file.is_dir() {
-            if fs::create_dir(&config_file).is_err() {
-                return Err(Error::other("-- failed to create a directory for the configuration files"));
-            }
+        if !config_file.is_dir() && fs::create_dir(&config_file).is_err() {
+            return Err(Error::other("-- failed to create a directory for the configuration files"));
         }
 
         // expand path to configuration file and default configuration file
@@ -181,29 +269,64 @@ impl Config {
 
         // write default configuration file if it does not exist
         if !default_file.is_file() {
-            let mut default_string = Self::DEFAULT_FILE_HEADER.to_owned();
-            default_string.push_str(&config.to_string());
-            _ = fs::write(&default_file, default_string);
+            _ = fs::write(&default_file, Self::default_string());
         }
 
-        // parse configuration file and populate config struct
-        // if the file is not found, navigate uses the defaults
-        if config_file.is_file() {
-            let config_str = match fs::read_to_string(&config_file) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
-            _ = config.parse_from_string(&config_str);
-        } else {
-            let default_config = match fs::read_to_string(&default_file) {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
-            _ = fs::write(&config_file, &default_config);
-            _ = config.parse_from_string(&default_config);
+        // the user file is created without settings, so the lower layers
+        // (system file and defaults) stay in effect until a setting is defined
+        if !config_file.is_file() {
+            _ = fs::write(&config_file, Self::USER_FILE_HEADER);
+        }
+        Ok(config_file)
+    }
+
+    /// returns the existing configuration files in the order they are applied
+    pub fn layer_files(file: Option<&Path>) -> Result<Vec<PathBuf>> {
+        let mut layers: Vec<PathBuf> = vec![PathBuf::from(Self::SYSTEM_CONFIG_FILE), Self::user_file(file)?];
+        if let Some(project_file) = Self::find_project_file() {
+            layers.push(project_file);
         }
+        layers.retain(|layer| layer.is_file());
+        Ok(layers)
+    }
+
+    /// returns the default configuration file with all settings and their documentation
+    pub fn default_string() -> String {
+        let mut default_string = Self::DEFAULT_FILE_HEADER.to_owned();
+        default_string.push_str(&Self::default().to_string());
+        default_string
+    }
+
+    /// returns the layer which overrides the setting `key` of the user file
+    /// `user_file`, i.e. the project file, the environment or the command line
+    pub fn overriding_source(&self, key: &str, user_file: &Path) -> Option<&String> {
+        self.sources.get(key).filter(|source| {
+            **source != user_file.display().to_string() && *source != Self::SYSTEM_CONFIG_FILE
+        })
+    }
+
+    /// merges `input` into the config and records `source` as origin of the
+    /// settings it defines, returns the problems found as `<source>:<line>:<column>: <message>`
+    pub fn merge_layer(&mut self, input: &str, source: &str) -> Vec<String> {
+        let (keys, diagnostics) = self.merge_from_string(input);
+        for key in keys {
+            self.sources.insert(key, source.to_owned());
+        }
+        diagnostics.iter()
+            .map(|diagnostic| if diagnostic.location == Location::default() {
+                format!("{}: {}", source, diagnostic)
+            } else {
+                format!("{}:{}", source, diagnostic)
+            })
+            .collect()
+    }
 
-        Ok(config)
+    /// searches the working directory and its parents for a project configuration file
+    fn find_project_file() -> Option<PathBuf> {
+        let directory = current_dir().ok()?;
+        directory.ancestors()
+            .map(|ancestor| ancestor.join(Self::PROJECT_CONFIG_NAME))
+            .find(|file| file.is_file())
     }
 
     /// formats and prints config to string
@@ -0,0 +1,200 @@
+//! integration scripts for the supported shells, embedded in the binary
+//! and printed by `navigate init <shell>`
+
+use clap::CommandFactory;
+use clap_complete::generate;
+
+use super::arguments::{Arguments, InitArgs};
+use super::shell::Shell;
+
+const BASH: &str = include_str!("../shell/navigate.bash");
+const ZSH: &str = include_str!("../shell/navigate.zsh");
+const FISH: &str = include_str!("../shell/navigate.fish");
+const NU: &str = include_str!("../shell/navigate.nu");
+
+/// arguments completed at runtime by the functions of the zsh script, as
+/// subcommand of `navigate`, nested subcommand (empty if none), id and function
+const DYNAMIC_VALUES: [(&str, &str, &str, &str); 5] = [
+    ("push", "", "path", "_navigate_push_path"),
+    ("pop", "", "num_entries", "_navigate_pop_counts"),
+    ("bookmark", "", "name", "_navigate_bookmarks"),
+    ("bookmark", "remove", "names", "_navigate_bookmarks"),
+    ("bookmark", "rename", "name", "_navigate_bookmarks"),
+];
+
+/// functions overriding `cd`, `{{push}}` is replaced like in the scripts
+const BASH_CD: &str = "
+function cd {
+    {{push}} \"$@\"
+}
+
+complete -F _navigate_push -o bashdefault -o dirnames cd
+";
+const ZSH_CD: &str = "
+function cd {
+    {{push}} \"$@\"
+}
+
+if (( $+functions[compdef] )); then
+    compdef _{{push}} cd
+fi
+";
+const FISH_CD: &str = "
+function cd --wraps {{push}}
+    {{push}} $argv
+end
+";
+const NU_CD: &str = "
+def --env --wrapped cd [...args: string@\"nu-complete navigate push\"] {
+    {{push}} ...$args
+}
+";
+
+/// prompt hooks calling `navigate observe` with the previous directory if the
+/// directory was changed without navigate
+const BASH_HOOK: &str = "
+__navigate_last_pwd=\"$PWD\"
+function __navigate_observe {
+    if [[ \"$PWD\" != \"$__navigate_last_pwd\" ]]; then
+        navigate --pid $$ --shell bash observe -- \"$__navigate_last_pwd\"
+        __navigate_last_pwd=\"$PWD\"
+    fi
+}
+
+if [[ \";${PROMPT_COMMAND[*]};\" != *\";__navigate_observe;\"* ]]; then
+    PROMPT_COMMAND=\"__navigate_observe${PROMPT_COMMAND:+;$PROMPT_COMMAND}\"
+fi
+";
+const ZSH_HOOK: &str = "
+function __navigate_observe {
+    (( ${__navigate_active:-0} )) && return
+    navigate --pid $$ --shell zsh observe -- \"$OLDPWD\"
+}
+
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd __navigate_observe
+";
+const FISH_HOOK: &str = "
+function __navigate_observe --on-variable PWD
+    set -q __navigate_active; and return
+    set -q dirprev[-1]; or return
+    navigate --pid $fish_pid --shell fish observe -- $dirprev[-1]
+end
+";
+const NU_HOOK: &str = "
+$env.config.hooks.env_change.PWD = ($env.config.hooks.env_change.PWD? | default [] | append { |before, after|
+    if $before != null and $after != ($env.__NAVIGATE_TARGET? | default \"\") {
+        ^navigate --pid $nu.pid --shell nu observe $before | ignore
+    }
+})
+";
+
+/// returns the integration script for the shell in `args` with the function
+/// names filled in
+pub fn script(args: &InitArgs) -> String {
+    let (template, cd, hook) = match args.shell {
+        Shell::Bash => (BASH, BASH_CD, BASH_HOOK),
+        Shell::Zsh => (ZSH, ZSH_CD, ZSH_HOOK),
+        Shell::Fish => (FISH, FISH_CD, FISH_HOOK),
+        Shell::Nu => (NU, NU_CD, NU_HOOK),
+    };
+    let mut script = template.replace("{{completions}}", &completions(args));
+    if args.cd {
+        script.push_str(cd);
+    }
+    if args.hook {
+        script.push_str(hook);
+    }
+
+    for (_, placeholder, name) in functions(args) {
+        script = script.replace(placeholder, &name);
+    }
+    script
+}
+
+/// subcommands of `navigate` with the placeholders and names of the functions calling them
+fn functions(args: &InitArgs) -> [(&'static str, &'static str, String); 5] {
+    let name = |name: &String| format!("{}{}", args.prefix, name);
+    [
+        ("push", "{{push}}", name(&args.push)),
+        ("pop", "{{pop}}", name(&args.pop)),
+        ("stack", "{{stack}}", name(&args.stack)),
+        ("bookmark", "{{book}}", name(&args.book)),
+        ("configuration", "{{navconfig}}", name(&args.navconfig)),
+    ]
+}
+
+/// completions generated from the arguments of the subcommands behind the functions
+fn completions(args: &InitArgs) -> String {
+    let shell = match args.shell {
+        Shell::Bash => clap_complete::Shell::Bash,
+        Shell::Zsh => clap_complete::Shell::Zsh,
+        Shell::Fish => clap_complete::Shell::Fish,
+        Shell::Nu => return String::new(),
+    };
+
+    let mut result = String::new();
+    for (subcommand, _, name) in functions(args) {
+        let mut command = match Arguments::command().find_subcommand(subcommand) {
+            Some(value) => without_hidden_subcommands(subcommand, value),
+            None => continue,
+        };
+        let mut buffer = Vec::<u8>::new();
+        generate(shell, &mut command, &name, &mut buffer);
+        let mut generated = String::from_utf8_lossy(&buffer).to_string();
+
+        match args.shell {
+            Shell::Bash => {
+                // the functions are registered by the script, with the runtime values added
+                if let Some(index) = generated.rfind("if [[ \"${BASH_VERSINFO[0]}\"") {
+                    generated.truncate(index);
+                }
+            },
+            Shell::Zsh => {
+                // `compdef` might not be available, the script checks before registering
+                if let Some(index) = generated.rfind("if [ \"$funcstack[1]\"") {
+                    generated.truncate(index);
+                }
+                generated = complete_dynamic_values(subcommand, &generated);
+            },
+            _ => {},
+        }
+        result.push_str(generated.trim_end());
+        result.push('\n');
+    }
+    result
+}
+
+/// copies `command` without the hidden subcommands (`completions`), which
+/// are not skipped by the generators
+fn without_hidden_subcommands(name: &'static str, command: &clap::Command) -> clap::Command {
+    let mut result = clap::Command::new(name)
+        .args(command.get_arguments().cloned())
+        .subcommands(command.get_subcommands().filter(|subcommand| !subcommand.is_hide_set()).cloned());
+    if let Some(about) = command.get_about() {
+        result = result.about(about.clone());
+    }
+    result
+}
+
+/// replaces the default action of the arguments in `DYNAMIC_VALUES` in the zsh
+/// completions generated for `subcommand`
+fn complete_dynamic_values(subcommand: &str, generated: &str) -> String {
+    // nested subcommands start with `(<name>)`, arguments are
+    // specified as `'<optional>:<id> -- <help>:<action>' \`
+    let mut nested = "";
+    let lines: Vec<String> = generated.lines().map(|line| {
+        let trimmed = line.trim();
+        if trimmed.starts_with('(') && trimmed.ends_with(')') {
+            nested = &trimmed[1..trimmed.len() - 1];
+        }
+        let dynamic = DYNAMIC_VALUES.iter().find(|(command, nested_command, id, _)| {
+            *command == subcommand && *nested_command == nested && line.contains(&format!(":{} -- ", id))
+        });
+        match dynamic {
+            Some((_, _, _, function)) => line.replace(":_default'", &format!(":{}'", function)),
+            None => line.to_string(),
+        }
+    }).collect();
+    lines.join("\n")
+}
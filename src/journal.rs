@@ -0,0 +1,226 @@
+//! append-only journal of bookmark mutations, used to show the
+//! history of the bookmarks and to undo changes
+
+use std::fs::{self, OpenOptions};
+use std::io::{Error, Result, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use dirs::config_dir;
+
+/// kind of operation recorded in the journal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalAction {
+    Add,
+    Remove,
+    Rename,
+    Clean,
+    Undo,
+}
+
+impl JournalAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JournalAction::Add => "add",
+            JournalAction::Remove => "remove",
+            JournalAction::Rename => "rename",
+            JournalAction::Clean => "clean",
+            JournalAction::Undo => "undo",
+        }
+    }
+
+    fn parse(string: &str) -> Option<Self> {
+        match string {
+            "add" => Some(JournalAction::Add),
+            "remove" => Some(JournalAction::Remove),
+            "rename" => Some(JournalAction::Rename),
+            "clean" => Some(JournalAction::Clean),
+            "undo" => Some(JournalAction::Undo),
+            _ => None,
+        }
+    }
+}
+
+/// a single bookmark change, `before` and `after` hold (name, path)
+/// `None` means the bookmark did not exist before/after the change
+/// `host` is the host pattern of the section the bookmark belongs to
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub host: Option<String>,
+    pub before: Option<(String, String)>,
+    pub after: Option<(String, String)>,
+}
+
+/// one recorded operation, which may consist of several changes
+/// (e.g. `clean` removes several bookmarks at once)
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub id: u64,
+    pub timestamp: u64,
+    pub action: JournalAction,
+    pub changes: Vec<Change>,
+    pub undone: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+    operations: Vec<Operation>,
+}
+
+impl Journal {
+    const JOURNAL_FILE_PATH: &str = "navigate/bookmarks_journal";
+
+    /// reads the journal file, a missing file is treated as empty journal
+    pub fn new() -> Result<Self> {
+        let mut path = match config_dir() {
+            Some(value) => value,
+            None => return Err(Error::other("-- failed to find configuration directory")),
+        };
+        path.push(Self::JOURNAL_FILE_PATH);
+
+        let mut journal = Journal {
+            path,
+            operations: Vec::<Operation>::new(),
+        };
+        if journal.path.is_file() {
+            let content = fs::read_to_string(&journal.path)?;
+            for line in content.lines() {
+                journal.parse_line(line);
+            }
+        }
+        Ok(journal)
+    }
+
+    /// returns all operations, oldest first
+    pub fn operations(&self) -> &Vec<Operation> {
+        &self.operations
+    }
+
+    /// returns the last `count` operations which have not been undone yet, newest first
+    pub fn undoable(&self, count: usize) -> Vec<Operation> {
+        self.operations.iter()
+            .rev()
+            .filter(|operation| !operation.undone && operation.action != JournalAction::Undo)
+            .take(count)
+            .cloned()
+            .collect()
+    }
+
+    /// appends an operation to the journal file
+    pub fn record(&mut self, action: JournalAction, changes: Vec<Change>) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let operation = Operation {
+            id: self.next_id(),
+            timestamp: now(),
+            action,
+            changes,
+            undone: false,
+        };
+        let mut lines = String::new();
+        for change in &operation.changes {
+            let (old_name, old_path) = change.before.clone().unwrap_or_default();
+            let (new_name, new_path) = change.after.clone().unwrap_or_default();
+            let host = change.host.clone().unwrap_or_default();
+            let fields = [old_name, old_path, new_name, new_path, host].map(|field| escape(&field));
+            lines.push_str(&format!("{}\t{}\t{}\t{}\n", operation.id, operation.timestamp, action.as_str(), fields.join("\t")));
+        }
+        self.append(&lines)?;
+        self.operations.push(operation);
+        Ok(())
+    }
+
+    /// marks the operations as undone and records the undo in the journal file
+    pub fn record_undo(&mut self, ids: &[u64]) -> Result<()> {
+        let id = self.next_id();
+        let timestamp = now();
+        let mut lines = String::new();
+        for target in ids {
+            lines.push_str(&format!("{}\t{}\t{}\t{}\n", id, timestamp, JournalAction::Undo.as_str(), target));
+        }
+        self.append(&lines)?;
+        for operation in self.operations.iter_mut() {
+            if ids.contains(&operation.id) {
+                operation.undone = true;
+            }
+        }
+        self.operations.push(Operation { id, timestamp, action: JournalAction::Undo, changes: Vec::new(), undone: false });
+        Ok(())
+    }
+
+    fn append(&self, lines: &str) -> Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(lines.as_bytes())?;
+        Ok(())
+    }
+
+    fn next_id(&self) -> u64 {
+        self.operations.last().map_or(1, |operation| operation.id + 1)
+    }
+
+    /// parses one line of the journal file, malformed lines are skipped
+    fn parse_line(&mut self, line: &str) {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 4 {
+            return;
+        }
+        let (Ok(id), Ok(timestamp), Some(action)) = (fields[0].parse::<u64>(), fields[1].parse::<u64>(), JournalAction::parse(fields[2])) else {
+            return;
+        };
+
+        let mut change: Option<Change> = None;
+        if action == JournalAction::Undo {
+            if let Ok(target) = fields[3].parse::<u64>() {
+                for operation in self.operations.iter_mut() {
+                    if operation.id == target {
+                        operation.undone = true;
+                    }
+                }
+            }
+        } else if fields.len() == 7 || fields.len() == 8 {
+            let fields: Vec<String> = fields.iter().map(|field| unescape(field)).collect();
+            let pair = |name: &String, path: &String| (!name.is_empty()).then(|| (name.clone(), path.clone()));
+            change = Some(Change {
+                host: fields.get(7).filter(|host| !host.is_empty()).cloned(),
+                before: pair(&fields[3], &fields[4]),
+                after: pair(&fields[5], &fields[6]),
+            });
+        } else {
+            return;
+        }
+
+        match self.operations.last_mut() {
+            Some(operation) if operation.id == id => operation.changes.extend(change),
+            _ => self.operations.push(Operation { id, timestamp, action, changes: change.into_iter().collect(), undone: false }),
+        }
+    }
+}
+
+/// seconds since the unix epoch
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs())
+}
+
+/// escapes characters which would break the line/tab separated journal format
+fn escape(field: &str) -> String {
+    field.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+fn unescape(field: &str) -> String {
+    let mut result = String::new();
+    let mut chars = field.chars();
+    while let Some(character) = chars.next() {
+        if character == '\\' {
+            match chars.next() {
+                Some('t') => result.push('\t'),
+                Some('n') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        } else {
+            result.push(character);
+        }
+    }
+    result
+}
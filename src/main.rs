@@ -1,8 +1,14 @@
 mod arguments;
 mod config;
 mod bookmarks;
+mod init;
+mod journal;
+mod mounts;
 mod stack;
 mod output;
+mod quote;
+mod records;
+mod shell;
 mod util;
 
 use arguments::*;
@@ -12,18 +18,36 @@ use bookmarks::*;
 use config_parser::*;
 use dirs::home_dir;
 use output::Output;
+use shell::Shell;
 use stack::Stack;
-use util::to_rooted;
+use util::{confirm, glob_match, to_rooted};
 use std::char;
 use std::env::current_dir;
-use std::io::{Error, Result};
+use std::fs;
+use std::io::{stderr, stdout, Error, IsTerminal, Result};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 fn main() -> Result<()> {
-    let mut output = Output::new();
-    let config = match Config::new() {
-        Ok(value) => value,
+    let mut output = Output::new(Shell::from_args(std::env::args()));
+    let parsed = Arguments::try_parse();
+    // without valid arguments, errors are reported with the configuration of the files
+    let (config_file, overrides) = match &parsed {
+        Ok(args) => (args.config.clone(), args.overrides.clone()),
+        Err(_) => (None, Vec::<String>::new()),
+    };
+    // the configured color mode is not known until the configuration is read
+    setup_color(ColorMode::default(), false, &mut output);
+    let (config, config_problems) = match Config::new(config_file.as_deref(), &overrides) {
+        Ok((value, diagnostics)) => {
+            // problems in the configuration file are reported on every call,
+            // the affected settings keep their default values
+            let problems = diagnostics.len();
+            for diagnostic in diagnostics {
+                output.push_warning(&format!("-- {}", diagnostic));
+            }
+            (value, problems)
+        },
         Err(error) => {
             // config object is not ready at this point so the style
             // has to be created by hand
@@ -32,7 +56,10 @@ fn main() -> Result<()> {
             return Ok(())
         }
     };
-    let args = match Arguments::try_parse() {
+    // styles are applied to output displayed on a terminal (see `ColorMode`)
+    let color = config.format.color;
+    setup_color(color, false, &mut output);
+    let args = match parsed {
         Ok(a) => a,
         Err(error) => {
             match error.kind() {
@@ -50,6 +77,18 @@ fn main() -> Result<()> {
             return Ok(());
         }
     };
+    let color = match &args.color {
+        Some(value) => ColorMode::parse(value).unwrap_or(color),
+        None => color,
+    };
+    setup_color(color, args.tty, &mut output);
+    // the integration script is printed as it is, it is not part of the output
+    if let Action::init(init_args) = &args.action {
+        print!("{}", init::script(init_args));
+        return Ok(());
+    }
+    // without `--pid` navigate is called directly by the shell, e.g. `cd "$(navigate resolve =1)"`
+    let pid = args.pid.unwrap_or_else(std::os::unix::process::parent_id);
     let mut bookmarks = match Bookmarks::new() {
         Ok(value) => value,
         Err(error) => {
@@ -58,11 +97,12 @@ fn main() -> Result<()> {
             return Ok(());
         }
     };
-    let mut stack = match Stack::new(&config, args.pid) {
+    let mut stack = match Stack::new(&config, pid) {
         Ok(stack) => stack,
         Err(_) => {
             output.push_error(&"-- failed to build stack".to_string());
-            return Err(Error::other(""));
+            output.print_output(Some(&config));
+            return Ok(());
         }
     };
     let res = match args.action {
@@ -70,11 +110,14 @@ fn main() -> Result<()> {
         Action::pop(pop_args) => handle_pop(&pop_args, &config, &mut stack, &mut output),
         Action::stack(stack_args) => handle_stack(&stack_args, &config, &mut stack, &mut output),
         Action::bookmark(bookmark_args) => handle_bookmark(&bookmark_args, &config, &mut bookmarks, &mut stack, &mut output),
-        Action::configuration => handle_config(&mut output),
+        Action::configuration(config_args) => handle_config(&config_args, config_file.as_deref(), &config, config_problems, &mut output),
+        Action::init(_) => Ok(()),
+        Action::resolve(resolve_args) => handle_resolve(&resolve_args, &mut bookmarks, &mut stack, &mut output),
+        Action::observe(observe_args) => handle_observe(&observe_args, &config, &mut stack),
     };
 
-    if res.is_err() {
-        output.push_error(&res.unwrap_err().to_string());
+    if let Err(error) = res {
+        output.push_error(&error.to_string());
     }
 
     // print output and command
@@ -83,6 +126,13 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// applies `mode` to styled output, `tty` is passed by the shell integration
+/// because the calling shell captures stdout to evaluate it
+fn setup_color(mode: ColorMode, tty: bool, output: &mut Output) {
+    set_color(mode.use_color(tty || stdout().is_terminal()));
+    output.set_color(mode.use_color(stderr().is_terminal()));
+}
+
 fn handle_push(args: &PushArgs, config: &Config, stack: &mut Stack, output: &mut Output) -> Result<()> {
     // paths arguments starting with `=` are interpreted as stack entry number
     const PREFIX: char = '=';
@@ -117,11 +167,11 @@ fn handle_push(args: &PushArgs, config: &Config, stack: &mut Stack, output: &mut
         }
     };
     if let Some(true) = args.show_stack {
-        output.push_info(&stack.to_formatted_string(&config)?);
+        output.push_info(&stack.to_formatted_string(config)?);
     } else if config.general.show_stack_on_push {
-        output.push_info(&stack.to_formatted_string(&config)?);
+        output.push_info(&stack.to_formatted_string(config)?);
     }
-    push_path(&path, stack, config, output)?;
+    push_path(&path, args.print, stack, config, output)?;
     Ok(())
 }
 
@@ -140,10 +190,7 @@ fn handle_pop(args: &PopArgs, config: &Config, stack: &mut Stack, output: &mut O
     } else if config.general.show_stack_on_push {
         output.push_info(&stack.to_formatted_string(config)?);
     }
-    output.push_command(&format!("cd -- {}", match path.to_str() {
-        Some(value) => value,
-        None => return Err(Error::other("-- failed to print popped path as string")),
-    }));
+    output.push_change_directory(&path);
     Ok(())
 }
 
@@ -155,93 +202,366 @@ fn handle_stack(args: &StackArgs, config: &Config, stack: &mut Stack, output: &m
                 output.push_info(&"stack cleared.".to_owned());
                 return Ok(());
             }
+            StackAction::completions(completion_args) => {
+                let shell = output.shell();
+                let entries: Vec<String> = stack.get_entries().iter().enumerate().map(|(n, path)| {
+                    if completion_args.describe {
+                        shell.describe(&format!("={}", n), &path.to_string_lossy())
+                    } else {
+                        format!("={}", n)
+                    }
+                }).collect();
+                output.push_info(&entries.join(if completion_args.describe { "\n" } else { " " }));
+                return Ok(());
+            }
         }
     }
     // retrieve stack
-    output.push_info(&stack.to_formatted_string(config)?);
+    if let Some(format) = args.format {
+        output.push_raw(&records::render(&stack.to_records(), format));
+    } else {
+        output.push_info(&stack.to_formatted_string(config)?);
+    }
     Ok(())
 }
 
 fn handle_bookmark(args: &BookmarkArgs, config: &Config, bookmarks: &mut Bookmarks, stack: &mut Stack, output: &mut Output) -> Result<()> {
     if let Some(action) = &args.bookmark_action {
         match action {
-            BookmarkAction::list => list_bookmarks(config, bookmarks, output)?,
+            BookmarkAction::list(args) => match args.format {
+                Some(format) => output.push_raw(&records::render(&bookmarks.to_records(args.all_hosts), format)),
+                None => list_bookmarks(args.all_hosts, config, bookmarks, output)?,
+            },
             BookmarkAction::add(args) => add_bookmarks(args, config, bookmarks, output)?,
             BookmarkAction::remove(args) => remove_bookmarks(args, config, bookmarks, output)?,
-            BookmarkAction::clean => bookmarks.remove_invalid_paths()?,
-            BookmarkAction::completions => println!("echo '{}'", bookmarks.get_bookmark_names()),
+            BookmarkAction::clean(args) => clean_bookmarks(args, config, bookmarks, output)?,
+            BookmarkAction::rename(args) => rename_bookmark(args, config, bookmarks, output)?,
+            BookmarkAction::history(args) => output.push_info(&bookmarks.history_to_formatted_string(args.count.unwrap_or(10), config)?),
+            BookmarkAction::undo(args) => undo_bookmarks(args, config, bookmarks, output)?,
+            BookmarkAction::completions(completion_args) => if completion_args.describe {
+                let shell = output.shell();
+                let descriptions: Vec<String> = bookmarks.get_bookmark_descriptions().iter()
+                    .map(|(name, path)| shell.describe(name, path))
+                    .collect();
+                output.push_info(&descriptions.join("\n"));
+            } else {
+                output.push_info(&bookmarks.get_bookmark_names());
+            },
         };
-    } else if args.name.is_some() { // handle `change to bookmark`
-        let path = bookmarks.get_path_by_name(args.name.as_ref().unwrap())?;
-        push_path(&path, stack, config, output)?;
+    } else if let Some(name) = &args.name { // handle `change to bookmark`
+        let path = bookmarks.get_path_by_name(name)?;
+        push_path(&path, false, stack, config, output)?;
     } else {
-        list_bookmarks(config, bookmarks, output)?;
+        list_bookmarks(false, config, bookmarks, output)?;
     }
     Ok(())
 }
 
-fn handle_config(output: &mut Output) -> Result<()> {
-    let config = Config::new();
-    output.push_info(&format!("config = {:#?}", config));
+/// `file` is the configuration file of `--config`, `problems` the number of problems
+/// found (and reported) while reading the configuration
+fn handle_config(args: &ConfigArgs, file: Option<&Path>, config: &Config, problems: usize, output: &mut Output) -> Result<()> {
+    if let Some(action) = &args.action {
+        match action {
+            ConfigAction::get(get_args) => {
+                let value = config.to_entries().into_iter()
+                    .find(|(key, _)| *key == get_args.key)
+                    .map(|(_, value)| value);
+                match value {
+                    Some(value) => output.push_info(&value),
+                    None => return Err(Error::other(format!("-- get : unknown setting `{}`", get_args.key))),
+                }
+            },
+            ConfigAction::set(set_args) => set_setting(set_args, file, config, output)?,
+            ConfigAction::edit => edit_config(file, output)?,
+            ConfigAction::check => {
+                // the problems are reported as warnings already
+                if problems > 0 {
+                    return Err(Error::other(format!("-- check : found {} problem(s) in the configuration", problems)));
+                }
+                let files: Vec<String> = Config::layer_files(file)?.iter().map(|path| path.display().to_string()).collect();
+                output.push_info(&format!("-- check : no problems found in {}", files.join(", ")));
+            },
+            ConfigAction::path(path_args) => {
+                let files = if path_args.all {
+                    Config::layer_files(file)?
+                } else {
+                    vec![Config::user_file(file)?]
+                };
+                let files: Vec<String> = files.iter().map(|path| path.display().to_string()).collect();
+                output.push_info(&files.join("\n"));
+            },
+            ConfigAction::defaults => output.push_info(&Config::default_string()),
+            ConfigAction::completions(completion_args) => {
+                let shell = output.shell();
+                let entries: Vec<String> = config.to_toml_entries().into_iter().map(|(key, value)| {
+                    if completion_args.describe {
+                        shell.describe(&key, &value)
+                    } else {
+                        key
+                    }
+                }).collect();
+                output.push_info(&entries.join(if completion_args.describe { "\n" } else { " " }));
+            },
+        }
+        return Ok(());
+    }
+    if let Some(format) = args.format {
+        let entries: Vec<records::Record> = config.to_entries().into_iter().map(|(key, value)| {
+            // layer which set the value, e.g. the path of a configuration file
+            let source = config.sources.get(&key).map_or("default", String::as_str).to_owned();
+            vec![
+                ("key", serde_json::Value::from(key)),
+                ("value", serde_json::Value::from(value)),
+                ("source", serde_json::Value::from(source)),
+            ]
+        }).collect();
+        output.push_raw(&records::render(&entries, format));
+        return Ok(());
+    }
+    // settings as toml, with the layer which set them if it is not the default
+    let lines: Vec<String> = config.to_toml_entries().into_iter().map(|(key, value)| {
+        match config.sources.get(&key) {
+            Some(source) => format!("{} = {} # {}", key, value, source),
+            None => format!("{} = {}", key, value),
+        }
+    }).collect();
+    output.push_info(&lines.join("\n"));
     Ok(())
 }
 
-fn list_bookmarks(config: &Config, bookmarks: &mut Bookmarks, output: &mut Output) -> Result<()> {
-    output.push_info(&bookmarks.to_formatted_string(config)?);
+/// validates the new value with the generated parser and writes it to the user file
+fn set_setting(args: &ConfigSetArgs, file: Option<&Path>, config: &Config, output: &mut Output) -> Result<()> {
+    let mut updated = config.clone();
+    let mut keys = Vec::<String>::new();
+    let diagnostics = match parse_override(&format!("{}={}", args.key, args.value)) {
+        Ok(map) => updated.merge_from_map(map, "", &mut keys),
+        Err(diagnostic) => vec![diagnostic],
+    };
+    if let Some(diagnostic) = diagnostics.first() {
+        return Err(Error::other(format!("-- set : {}", diagnostic)));
+    }
+    let value = match updated.to_toml_entries().into_iter().find(|(key, _)| *key == args.key) {
+        Some((_, value)) => value,
+        None => return Err(Error::other(format!("-- set : unknown setting `{}`", args.key))),
+    };
+
+    let user_file = Config::user_file(file)?;
+    let content = fs::read_to_string(&user_file)?;
+    let content = match set_toml_value(&content, &args.key, &value) {
+        Ok(value) => value,
+        Err(diagnostic) => return Err(Error::other(format!("-- set : {}:{}", user_file.display(), diagnostic))),
+    };
+    fs::write(&user_file, content)?;
+    output.push_info(&format!("-- set `{}` to {} in {}", args.key, value, user_file.display()));
+    if let Some(source) = config.overriding_source(&args.key, &user_file) {
+        output.push_warning(&format!("-- `{}` is overridden by {}", args.key, source));
+    }
+    Ok(())
+}
+
+/// opens the user file in the editor of the user and checks it afterwards
+fn edit_config(file: Option<&Path>, output: &mut Output) -> Result<()> {
+    let user_file = Config::user_file(file)?;
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_owned());
+    let mut words = editor.split_whitespace();
+    let program = match words.next() {
+        Some(value) => value,
+        None => return Err(Error::other("-- edit : no editor configured, set `$VISUAL` or `$EDITOR`")),
+    };
+    // stdout is captured by the shell integration, the editor needs the terminal
+    let terminal = match fs::File::options().read(true).write(true).open("/dev/tty") {
+        Ok(value) => value,
+        Err(_) => return Err(Error::other("-- edit : no terminal available")),
+    };
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&user_file)
+        .stdin(terminal.try_clone()?)
+        .stdout(terminal)
+        .status()?;
+    if !status.success() {
+        return Err(Error::other(format!("-- edit : `{}` exited with {}", editor, status)));
+    }
+
+    let content = fs::read_to_string(&user_file)?;
+    let diagnostics = Config::default().merge_layer(&content, &user_file.display().to_string());
+    if !diagnostics.is_empty() {
+        for diagnostic in &diagnostics {
+            output.push_warning(&format!("-- {}", diagnostic));
+        }
+        return Err(Error::other(format!("-- edit : found {} problem(s) in {}", diagnostics.len(), user_file.display())));
+    }
+    output.push_info(&format!("-- edit : no problems found in {}", user_file.display()));
+    Ok(())
+}
+
+fn list_bookmarks(all_hosts: bool, config: &Config, bookmarks: &mut Bookmarks, output: &mut Output) -> Result<()> {
+    if all_hosts {
+        output.push_info(&bookmarks.all_hosts_to_formatted_string(config)?);
+    } else {
+        output.push_info(&bookmarks.to_formatted_string(config)?);
+    }
     Ok(())
 }
 
 fn add_bookmarks(args: &BookmarkSubArgs, config: &Config, bookmarks: &mut Bookmarks, output: &mut Output) -> Result<()> {
-    let mut path : PathBuf = match PathBuf::from_str(&args.path.join(" ")) {
+    let path : PathBuf = match PathBuf::from_str(&args.path.join(" ")) {
         Ok(value) => value,
         Err(error) => return Err(Error::other(error.to_string())),
     };
     if args.name == "add"
         || args.name == "remove"
         || args.name == "clean"
+        || args.name == "rename"
+        || args.name == "history"
+        || args.name == "undo"
         || args.name == "help"
         || args.name == "completions" {
-        return Err(Error::other("-- `add`, `remove`, `clean`, `help` and `completions` are subcommands and cant be used as bookmarknames"));
+        return Err(Error::other("-- `add`, `remove`, `clean`, `rename`, `history`, `undo`, `help` and `completions` are subcommands and cannot be used as bookmarknames"));
     }
-    bookmarks.add_bookmark(&args.name, &path)?;
+    let mut paths: Vec<PathBuf> = vec![path];
+    paths.extend(args.fallback.iter().map(PathBuf::from));
+    bookmarks.add_bookmark(&args.name, &paths, args.host.as_ref())?;
 
     if config.general.show_entries_on_bookmark {
         output.push_info(&bookmarks.to_formatted_string(config)?);
     } else {
-        _ = to_rooted(&mut path);
-        output.push_info(&format!("added bookmark `{}{}{} = {}{}{}`.",
-            generate_style_sequence(Some(STYLES.set.bold), None, None), args.name, RESET_SEQ,
-            generate_style_sequence(Some(STYLES.set.italic), None, None), path.to_str().unwrap(), RESET_SEQ));
+        for path in paths.iter_mut() {
+            _ = Bookmark::normalize_path(path);
+        }
+        let paths: Vec<String> = paths.iter().map(|path| Bookmark::escape(&path.to_string_lossy())).collect();
+        output.push_info(&format!("added bookmark `{} = {}`.",
+            apply_format(&args.name, &config.styles.bookmarks_name_style),
+            apply_format(&Bookmark::join_candidates(&paths), &config.styles.bookmarks_path_style)));
     }
 
     Ok(())
 }
 
-fn remove_bookmarks(args: &BookmarkSubArgs, config: &Config, bookmarks: &mut Bookmarks, output: &mut Output) -> Result<()> {
-    let path = bookmarks.remove_bookmark(&args.name)?;
+fn remove_bookmarks(args: &BookmarkRemoveArgs, config: &Config, bookmarks: &mut Bookmarks, output: &mut Output) -> Result<()> {
+    let (removed, unmatched) = bookmarks.remove_bookmarks(&args.names)?;
+
+    for pattern in unmatched {
+        output.push_warning(&format!("-- no bookmark matches `{}`", pattern));
+    }
+    output.push_info(&format!("removed bookmarks:\n{}", Bookmarks::entries_to_formatted_string(&removed, config)?));
+    if config.general.show_entries_on_bookmark {
+        output.push_info(&bookmarks.to_formatted_string(config)?);
+    }
+    Ok(())
+}
+
+fn clean_bookmarks(args: &BookmarkCleanArgs, config: &Config, bookmarks: &mut Bookmarks, output: &mut Output) -> Result<()> {
+    let invalid = bookmarks.get_invalid_bookmarks(args.include_offline);
+    if invalid.is_empty() {
+        output.push_info(&"-- there are no invalid bookmarks".to_owned());
+        return Ok(());
+    }
+    let formatted = Bookmarks::entries_to_formatted_string(&invalid, config)?;
+    if args.dry_run {
+        output.push_info(&format!("bookmarks to be removed:\n{}", formatted));
+        return Ok(());
+    }
+    if !args.yes && !confirm(&format!("bookmarks to be removed:\n{}remove {} invalid bookmark(s)?", formatted, invalid.len()))? {
+        output.push_info(&"-- aborted, no bookmarks removed".to_owned());
+        return Ok(());
+    }
+
+    let removed = bookmarks.remove_invalid_paths(args.include_offline)?;
+    output.push_info(&format!("removed bookmarks:\n{}", Bookmarks::entries_to_formatted_string(&removed, config)?));
+    if config.general.show_entries_on_bookmark {
+        output.push_info(&bookmarks.to_formatted_string(config)?);
+    }
+    Ok(())
+}
+
+fn rename_bookmark(args: &BookmarkRenameArgs, config: &Config, bookmarks: &mut Bookmarks, output: &mut Output) -> Result<()> {
+    let bookmark = bookmarks.rename_bookmark(&args.name, &args.new_name)?;
 
     if config.general.show_entries_on_bookmark {
         output.push_info(&bookmarks.to_formatted_string(config)?);
     } else {
-        output.push_info(&format!("removed bookmark `{}{}{}{}{}`.", generate_style_sequence(Some(STYLES.set.bold), None, None), args.name, config.format.bookmarks_separator, path.to_str().unwrap(), RESET_SEQ));
+        output.push_info(&format!("renamed bookmark `{}` to `{}{}{}`.", args.name,
+            apply_format(&args.new_name, &config.styles.bookmarks_name_style),
+            apply_format(&config.format.bookmarks_separator, &config.styles.bookmarks_seperator_style),
+            apply_format(&bookmark.to_file_string(), &config.styles.bookmarks_path_style)));
+    }
+    Ok(())
+}
+
+fn undo_bookmarks(args: &BookmarkHistoryArgs, config: &Config, bookmarks: &mut Bookmarks, output: &mut Output) -> Result<()> {
+    let operations = bookmarks.undo(args.count.unwrap_or(1))?;
+
+    let actions: Vec<&str> = operations.iter().map(|operation| operation.action.as_str()).collect();
+    output.push_info(&format!("reverted `{}`.", actions.join("`, `")));
+    if config.general.show_entries_on_bookmark {
+        output.push_info(&bookmarks.to_formatted_string(config)?);
     }
     Ok(())
 }
 
 /// push path to stack and print command to navigate to provided path
-fn push_path(path: &Path, stack: &mut Stack, _config: &Config, output: &mut Output) -> Result<()> {
+/// pushes the current directory and changes to `path`, or
+/// prints `path` instead of changing to it if `print` is set
+fn push_path(path: &Path, print: bool, stack: &mut Stack, _config: &Config, output: &mut Output) -> Result<()> {
     let mut path = path.to_path_buf();
     let mut current_path: PathBuf = current_dir()?;
     to_rooted(&mut path)?;
     to_rooted(&mut current_path)?;
     if !path.is_dir() {
         return Err(Error::other("-- invalid path argument"));
-    } else if !(path == current_path) {
+    } else if path != current_path {
         stack.push_entry(&current_path)?;
-        output.push_command(&format!("cd -- '{}'", match path.canonicalize()?.to_str() {
-            Some(value) => value,
-            None => return Err(Error::other("-- failed to print provided path as string")),
-        }));
+        if !print {
+            output.push_change_directory(&path.canonicalize()?);
+        }
+    }
+    if print {
+        output.push_raw(&format!("{}\n", path.canonicalize()?.to_string_lossy()));
+    }
+    Ok(())
+}
+
+fn handle_resolve(args: &ResolveArgs, bookmarks: &mut Bookmarks, stack: &mut Stack, output: &mut Output) -> Result<()> {
+    let target = &args.target;
+    // names without path punctuation are bookmarks, if such a bookmark exists
+    let is_bookmark = !target.contains('/') && !target.starts_with('.') && bookmarks.active_bookmarks().contains_key(target);
+
+    let mut path: PathBuf = if let Some(number) = target.strip_prefix('=') {
+        let number: usize = match number.parse() {
+            Ok(value) => value,
+            Err(_) => return Err(Error::other(format!("-- `{}` is not a valid stack entry number", number))),
+        };
+        stack.get_entry_by_number(number)?.to_path_buf()
+    } else if is_bookmark {
+        bookmarks.get_path_by_name(target)?
+    } else {
+        PathBuf::from(target)
+    };
+    to_rooted(&mut path)?;
+    if !path.is_dir() {
+        return Err(Error::other(format!("-- `{}` does not resolve to a directory", target)));
+    }
+    output.push_raw(&format!("{}\n", path.canonicalize()?.to_string_lossy()));
+    Ok(())
+}
+
+fn handle_observe(args: &ObserveArgs, config: &Config, stack: &mut Stack) -> Result<()> {
+    let mut previous = PathBuf::from(&args.previous);
+    let mut current_path: PathBuf = current_dir()?;
+    to_rooted(&mut previous)?;
+    to_rooted(&mut current_path)?;
+
+    let previous_string = previous.to_string_lossy();
+    let ignored = config.general.observe_ignore_paths.iter()
+        .any(|pattern| glob_match(pattern, &previous_string));
+    // the newest entry equals `previous` if the change was made by navigate
+    let recorded = stack.get_entries().first().is_some_and(|entry| **entry == previous);
+    if previous == current_path || !previous.is_dir() || ignored || recorded {
+        return Ok(());
     }
+    stack.push_entry(&previous)?;
     Ok(())
 }
@@ -0,0 +1,125 @@
+//! detection of paths on removable or network file systems which
+//! are currently not mounted
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+const FSTAB_PATH: &str = "/etc/fstab";
+
+/// directories below which removable file systems are mounted without an
+/// entry in fstab (e.g. by udisks or by hand)
+const MOUNT_ROOTS: [&str; 3] = ["/mnt", "/media", "/run/media"];
+
+/// mount points currently mounted
+fn mounted() -> &'static Vec<PathBuf> {
+    static MOUNTED: OnceLock<Vec<PathBuf>> = OnceLock::new();
+    // the mount point is the fifth field of each line in mountinfo
+    MOUNTED.get_or_init(|| read_mount_points(MOUNTINFO_PATH, 4))
+}
+
+/// mount points configured in fstab
+fn configured() -> &'static Vec<PathBuf> {
+    static CONFIGURED: OnceLock<Vec<PathBuf>> = OnceLock::new();
+    // the mount point is the second field of each line in fstab
+    CONFIGURED.get_or_init(|| read_mount_points(FSTAB_PATH, 1))
+}
+
+/// reads the mount points from the column `field` of a mount table file
+fn read_mount_points(file: &str, field: usize) -> Vec<PathBuf> {
+    let content = fs::read_to_string(file).unwrap_or_default();
+    content.lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| line.split_whitespace().nth(field))
+        .filter(|mount_point| mount_point.starts_with('/'))
+        .map(|mount_point| PathBuf::from(unescape_octal(mount_point)))
+        .collect()
+}
+
+/// replaces the octal escapes used in mount tables (e.g. `\040` for space)
+fn unescape_octal(string: &str) -> String {
+    let bytes = string.as_bytes();
+    let mut result = Vec::<u8>::new();
+    let mut n = 0;
+    while n < bytes.len() {
+        if bytes[n] == b'\\' && n + 4 <= bytes.len() {
+            let digits = std::str::from_utf8(&bytes[n + 1..n + 4]).unwrap_or_default();
+            if let Ok(value) = u8::from_str_radix(digits, 8) {
+                result.push(value);
+                n += 4;
+                continue;
+            }
+        }
+        result.push(bytes[n]);
+        n += 1;
+    }
+    String::from_utf8_lossy(&result).to_string()
+}
+
+/// returns the closest ancestor of `path` which exists
+fn closest_existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().skip(1).find(|ancestor| ancestor.is_dir())
+}
+
+/// checks whether a non existing `path` lies on a file system which is currently
+/// not mounted (e.g. an unplugged usb drive or an unreachable network share)
+///
+/// this is the case if the path is below a mount point from fstab which is not
+/// mounted (see `/proc/self/mountinfo`), or if its closest existing ancestor
+/// is one of the usual mount roots (`MOUNT_ROOTS`: `/mnt`, `/media`, `/run/media`)
+/// or a directory directly below one (e.g. `/media/<user>`) and not a mount
+/// point itself, deleted directories anywhere else are missing, not offline
+pub fn is_offline(path: &Path) -> bool {
+    is_offline_with(path, mounted(), configured())
+}
+
+fn is_offline_with(path: &Path, mounted: &[PathBuf], configured: &[PathBuf]) -> bool {
+    if path.exists() {
+        return false;
+    }
+    if configured.iter()
+        .filter(|mount_point| mount_point.as_os_str() != "/")
+        .any(|mount_point| path.starts_with(mount_point) && !mounted.contains(mount_point)) {
+        return true;
+    }
+
+    let ancestor = match closest_existing_ancestor(path) {
+        Some(value) => value,
+        None => return false,
+    };
+    if mounted.iter().any(|mount_point| mount_point == ancestor) {
+        // the file system holding the path is mounted, so the path is missing
+        return false;
+    }
+    MOUNT_ROOTS.iter().any(|root| {
+        let root = Path::new(root);
+        // e.g. `/media` as well as `/media/<user>`
+        ancestor == root || ancestor.parent() == Some(root)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deleted_directory_is_not_offline() {
+        let directory = std::env::temp_dir().join(format!("navigate-mounts-{}", std::process::id()));
+        let path = directory.join("p").join("q");
+        fs::create_dir_all(&path).unwrap();
+        fs::remove_dir(&path).unwrap();
+        // the closest existing ancestor `p` is an empty directory
+        let offline = is_offline_with(&path, &[PathBuf::from("/")], &[PathBuf::from("/")]);
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(!offline);
+    }
+
+    #[test]
+    fn unmounted_fstab_entry_is_offline() {
+        let path = Path::new("/navigate-test-share/documents");
+        let configured = [PathBuf::from("/"), PathBuf::from("/navigate-test-share")];
+        assert!(is_offline_with(path, &[PathBuf::from("/")], &configured));
+        assert!(!is_offline_with(path, &configured, &configured));
+    }
+}
@@ -1,45 +1,94 @@
 #![allow(unused)]
 
 use clap::builder::EnumValueParser;
-use config_parser::{apply_format, parse_ansi_set, parse_ansi_unset};
+use config_parser::apply_format_with;
+use serde::Serialize;
 
 use super::config::*;
+use super::shell::Shell;
 
 use std::backtrace::Backtrace;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// commands for the calling shell, rendered in the dialect of the shell
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// change the working directory of the shell
+    ChangeDirectory(PathBuf),
+}
+
+/// commands and information serialized as a record, for shells which
+/// cannot evaluate the output as a script (nushell)
+/// warnings and errors are kept apart from `info`, the wrapper prints
+/// them to stderr and raises the errors
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    /// directory the shell has to change to
+    directory: Option<String>,
+    info: &'a [String],
+    warning: &'a [String],
+    error: &'a [String],
+}
 
 /// takes strings to output because the application is
 /// called by a script which executes the output with `eval`
 pub struct Output {
-    /// takes direct command output with custom formatting
-    /// **NOTE** - strings in `command` are not wrapped
-    /// with `echo` or similar and thus are interpretted as
-    /// commands
-    command: Vec<String>,
-    /// takes formatted output to be printed with `echo`
-    /// strings in `info` are prepended with `echo` but
-    /// do not get any formatting applied
+    /// dialect of the shell evaluating the output
+    shell: Shell,
+    /// takes commands to be executed by the calling shell
+    /// **NOTE** - commands are rendered in the dialect of
+    /// `shell` and interpretted as commands
+    command: Vec<Command>,
+    /// takes formatted output to be printed with `printf` by the
+    /// calling shell, strings in `info` are printed as they are
+    /// and do not get any formatting applied
     info: Vec<String>,
     /// takes warnings about command input or the state of
-    /// application
+    /// application, printed to stderr
     warning: Vec<String>,
     /// takes error messages about command input or the
-    /// state of application
+    /// state of application, printed to stderr
     error: Vec<String>,
+    /// takes machine readable output (`--format`), which is printed
+    /// as it is instead of being evaluated by the shell
+    raw: Option<String>,
+    /// style warnings and errors, decided separately because
+    /// they are printed to stderr
+    color: bool,
 }
 
 impl Output {
-    pub fn new() -> Self {
+    pub fn new(shell: Shell) -> Self {
         Self {
-            command: Vec::<String>::new(),
+            shell,
+            command: Vec::<Command>::new(),
             info: Vec::<String>::new(),
             warning: Vec::<String>::new(),
             error: Vec::<String>::new(),
+            raw: None,
+            color: true,
         }
     }
 
-    /// push a command to the output pipeline
-    pub fn push_command(&mut self, command: &String) {
-        self.command.push(command.to_string());
+    /// returns the dialect the output is rendered in
+    pub fn shell(&self) -> Shell {
+        self.shell
+    }
+
+    /// enable or disable styles of warnings and errors
+    pub fn set_color(&mut self, color: bool) {
+        self.color = color;
+    }
+
+    /// push a command to change the working directory to the output pipeline
+    pub fn push_change_directory(&mut self, path: &Path) {
+        self.command.push(Command::ChangeDirectory(path.to_path_buf()));
+    }
+
+    /// set machine readable output, printed instead of the commands
+    pub fn push_raw(&mut self, raw: &str) {
+        self.raw = Some(raw.to_owned());
     }
 
     /// push an information to the output pipeline
@@ -57,8 +106,11 @@ impl Output {
         self.error.push(error.to_string());
     }
 
-    /// format and print styled output
+    /// format and print styled output, warnings and errors are printed to stderr
+    /// directly (or part of the record for nushell), stdout only carries the
+    /// commands for the calling shell
     /// NOTE - this will execute any commands held by `command`
+    /// NOTE - exits with status 1 if any error was pushed
     pub fn print_output(&mut self, config: Option<&Config>) {
         let default = Config::default();
         let config = if let Some(value) = config {
@@ -67,29 +119,67 @@ impl Output {
             &default
         };
 
-        if !self.error.is_empty() {
-            self.push_command(&"false".to_owned());
+        let in_record = self.raw.is_none() && self.shell == Shell::Nu;
+        if !in_record {
+            for warning in &self.warning {
+                eprintln!("{}", apply_format_with(warning, &config.styles.warning_style, self.color));
+            }
+            for error in &self.error {
+                eprintln!("{}", apply_format_with(error, &config.styles.error_style, self.color));
+            }
         }
 
-        let mut output: Vec<String> = Vec::<String>::new();
-
-        if !self.info.is_empty() {
-            let mut info: String = self.info.iter().map(|entry| format!("echo '{}'", entry)).collect::<Vec<String>>().join(" && ");
-            output.push(info);
-        }
-        if !self.warning.is_empty() {
-        let mut warning: String = self.warning.iter().map(|entry| apply_format(entry, &config.styles.warning_style).unwrap()).map(|entry| format!("echo '{}'", entry)).collect::<Vec<String>>().join(" && ");
-            output.push(warning);
+        if let Some(raw) = &self.raw {
+            // machine readable output is not evaluated
+            for info in &self.info {
+                eprintln!("{}", info);
+            }
+            print!("{}", raw);
+        } else {
+            match self.shell {
+                Shell::Nu => println!("{}", self.to_record()),
+                _ => {
+                    let mut script = self.to_script();
+                    if !script.is_empty() {
+                        script.push(b'\n');
+                        let _ = std::io::stdout().write_all(&script);
+                    }
+                },
+            }
         }
+
         if !self.error.is_empty() {
-            let mut error: String = self.error.iter().map(|entry| apply_format(entry, &config.styles.error_style).unwrap()).map(|entry| format!("echo '{}'", entry)).collect::<Vec<String>>().join(" && ");
-            output.push(error);
+            let _ = std::io::stdout().flush();
+            std::process::exit(1);
         }
-        if !self.command.is_empty() {
-            let mut command: String = self.command.join(" && ");
-            output.push(command);
+    }
+
+    /// renders the output as a script in the dialect of `shell`
+    fn to_script(&self) -> Vec<u8> {
+        let shell = self.shell;
+        let mut output: Vec<Vec<u8>> = Vec::<Vec<u8>>::new();
+
+        output.extend(self.info.iter().map(|entry| shell.print(entry)));
+        for command in &self.command {
+            match command {
+                Command::ChangeDirectory(path) => output.push(shell.change_directory(path)),
+            }
         }
 
-        println!("{}", output.join(" && "));
+        shell.join(&output)
+    }
+
+    /// renders the output as a json record
+    fn to_record(&self) -> String {
+        let directory = self.command.iter().rev().map(|command| match command {
+            Command::ChangeDirectory(path) => path.to_string_lossy().to_string(),
+        }).next();
+        let record = Record {
+            directory,
+            info: &self.info,
+            warning: &self.warning,
+            error: &self.error,
+        };
+        serde_json::to_string(&record).unwrap_or_default()
     }
 }
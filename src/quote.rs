@@ -0,0 +1,130 @@
+//! quoting of arbitrary bytes for the supported shells, every string
+//! which is part of a command evaluated by the calling shell has to pass
+//! through `quote`, otherwise it could inject commands
+
+use super::shell::Shell;
+
+/// quotes `bytes` so `shell` reads them as a single literal word
+pub fn quote(shell: Shell, bytes: &[u8]) -> Vec<u8> {
+    // a nul byte can not be part of a shell word (nor of a path)
+    let bytes: Vec<u8> = bytes.iter().copied().filter(|byte| *byte != 0).collect();
+    match shell {
+        Shell::Bash | Shell::Zsh => quote_posix(&bytes),
+        Shell::Fish => quote_fish(&bytes),
+        Shell::Nu => quote_nu(&String::from_utf8_lossy(&bytes)),
+    }
+}
+
+/// nothing is special within single quotes (not even invalid utf-8), a
+/// single quote is written by closing, escaping and reopening the quotes
+fn quote_posix(bytes: &[u8]) -> Vec<u8> {
+    let mut result = vec![b'\''];
+    for byte in bytes {
+        if *byte == b'\'' {
+            result.extend_from_slice(b"'\\''");
+        } else {
+            result.push(*byte);
+        }
+    }
+    result.push(b'\'');
+    result
+}
+
+/// fish interprets `\\` and `\'` within single quotes, bytes which are not
+/// valid utf-8 are written as `\xHH` escapes outside of the quotes
+fn quote_fish(bytes: &[u8]) -> Vec<u8> {
+    let mut result = vec![b'\''];
+    for chunk in bytes.utf8_chunks() {
+        for byte in chunk.valid().bytes() {
+            if byte == b'\\' || byte == b'\'' {
+                result.push(b'\\');
+            }
+            result.push(byte);
+        }
+        if !chunk.invalid().is_empty() {
+            result.push(b'\'');
+            for byte in chunk.invalid() {
+                result.extend_from_slice(format!("\\x{:02x}", byte).as_bytes());
+            }
+            result.push(b'\'');
+        }
+    }
+    result.push(b'\'');
+    result
+}
+
+/// raw strings have no escapes, they are delimited by enough `#`
+/// (strings in nushell are utf-8, invalid bytes are replaced)
+fn quote_nu(string: &str) -> Vec<u8> {
+    let mut hashes = String::from("#");
+    while string.contains(&format!("'{}", hashes)) {
+        hashes.push('#');
+    }
+    format!("r{}'{}'{}", hashes, string, hashes).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+    use std::process::Command;
+
+    /// names a malicious stack or bookmark file could contain
+    const HOSTILE: [&[u8]; 10] = [
+        b"it's",
+        b"'; touch pwned; '",
+        b"$(touch pwned)",
+        b"`touch pwned`",
+        b"back\\slash\\'",
+        b"new\nline",
+        b"tab\there",
+        b"--option",
+        b"",
+        b"invalid \xff\xfe utf-8",
+    ];
+
+    /// evaluates `printf %s <quoted>` in `shell` and returns what was printed,
+    /// fails if the shell is not installed
+    fn evaluate(shell: Shell, program: &str, bytes: &[u8]) -> Vec<u8> {
+        let mut script = b"printf %s ".to_vec();
+        script.extend(quote(shell, bytes));
+        let script = OsString::from_vec(script);
+        let output = Command::new(program).arg("-c").arg(script).output()
+            .unwrap_or_else(|error| panic!("failed to run {}: {}", program, error));
+        assert!(output.status.success(), "{} failed: {}", program, String::from_utf8_lossy(&output.stderr));
+        output.stdout
+    }
+
+    #[test]
+    fn bash_quoting_round_trips() {
+        for bytes in HOSTILE {
+            assert_eq!(evaluate(Shell::Bash, "bash", bytes), bytes);
+        }
+    }
+
+    #[test]
+    #[ignore = "needs zsh, run with `cargo test -- --ignored`"]
+    fn zsh_quoting_round_trips() {
+        for bytes in HOSTILE {
+            assert_eq!(evaluate(Shell::Zsh, "zsh", bytes), bytes);
+        }
+    }
+
+    #[test]
+    #[ignore = "needs fish, run with `cargo test -- --ignored`"]
+    fn fish_quoting_round_trips() {
+        for bytes in HOSTILE {
+            assert_eq!(evaluate(Shell::Fish, "fish", bytes), bytes);
+        }
+    }
+
+    #[test]
+    fn quoted_strings_are_single_words() {
+        assert_eq!(quote(Shell::Bash, b"it's"), b"'it'\\''s'");
+        assert_eq!(quote(Shell::Fish, b"it's \\"), b"'it\\'s \\\\'");
+        assert_eq!(quote(Shell::Fish, b"a\xffb"), b"'a'\\xff'b'");
+        assert_eq!(quote(Shell::Nu, b"a'#b"), b"r##'a'#b'##");
+        assert_eq!(quote(Shell::Bash, b"nul\0byte"), b"'nulbyte'");
+    }
+}
@@ -0,0 +1,47 @@
+//! machine readable records of the stack, the bookmarks and the
+//! configuration, for `--format`
+
+use serde_json::{Map, Value};
+
+use super::arguments::Format;
+
+/// fields of a record in the order they are printed
+pub type Record = Vec<(&'static str, Value)>;
+
+/// renders `records` in `format`
+pub fn render(records: &[Record], format: Format) -> String {
+    match format {
+        Format::json => {
+            let objects: Vec<Value> = records.iter().map(|record| {
+                Value::Object(record.iter().map(|(key, value)| (key.to_string(), value.clone())).collect::<Map<String, Value>>())
+            }).collect();
+            serde_json::to_string_pretty(&objects).unwrap_or_default() + "\n"
+        },
+        Format::tsv => records.iter().map(|record| join_fields(record, true) + "\n").collect(),
+        Format::null => records.iter().map(|record| join_fields(record, false) + "\0").collect(),
+    }
+}
+
+/// joins the fields of `record` with tabs, tabs (and newlines if `escape_newlines`)
+/// within values are escaped
+fn join_fields(record: &Record, escape_newlines: bool) -> String {
+    let fields: Vec<String> = record.iter().map(|(_, value)| {
+        let field = field_to_string(value).replace('\\', "\\\\").replace('\t', "\\t");
+        if escape_newlines {
+            field.replace('\n', "\\n")
+        } else {
+            field
+        }
+    }).collect();
+    fields.join("\t")
+}
+
+/// plain representation of a value, lists are joined with `|` like in the bookmarks file
+fn field_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(string) => string.clone(),
+        Value::Array(values) => values.iter().map(field_to_string).collect::<Vec<String>>().join("|"),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,77 @@
+//! shell dialects the output of navigate can be rendered in
+
+use clap::ValueEnum;
+
+use super::quote::quote;
+
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// shell which evaluates the output of navigate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Shell {
+    #[default]
+    Bash,
+    Zsh,
+    Fish,
+    /// nushell cannot evaluate scripts, the output is a json record
+    /// consumed by the wrapper printed by `navigate init nu`
+    Nu,
+}
+
+impl Shell {
+    /// determines the shell from the raw command line arguments, used to report
+    /// errors which occur before (or while) the arguments are parsed
+    pub fn from_args(args: impl Iterator<Item = String>) -> Self {
+        let args: Vec<String> = args.collect();
+        for (n, arg) in args.iter().enumerate() {
+            let value = if let Some(value) = arg.strip_prefix("--shell=") {
+                Some(value.to_string())
+            } else if arg == "--shell" {
+                args.get(n + 1).cloned()
+            } else {
+                None
+            };
+            if let Some(value) = value {
+                return Shell::from_str(&value, true).unwrap_or_default();
+            }
+        }
+        Shell::default()
+    }
+
+    /// command to print `message` followed by a newline
+    pub fn print(&self, message: &str) -> Vec<u8> {
+        let mut command = match self {
+            Shell::Nu => b"print ".to_vec(),
+            _ => b"printf '%s\\n' ".to_vec(),
+        };
+        command.extend(quote(*self, message.as_bytes()));
+        command
+    }
+
+    /// command to change the working directory to `path`
+    pub fn change_directory(&self, path: &Path) -> Vec<u8> {
+        let mut command = match self {
+            Shell::Nu => b"cd ".to_vec(),
+            // `builtin`, the integration might override `cd` with a function calling navigate
+            _ => b"builtin cd -- ".to_vec(),
+        };
+        command.extend(quote(*self, path.as_os_str().as_bytes()));
+        command
+    }
+
+    /// joins commands, one per line so each command is executed
+    /// independently of the ones before
+    pub fn join(&self, commands: &[Vec<u8>]) -> Vec<u8> {
+        commands.join(&b'\n')
+    }
+
+    /// formats a completion candidate with its description
+    /// (`value:description` for zsh' `_describe`, tab separated for fish and nushell)
+    pub fn describe(&self, value: &str, description: &str) -> String {
+        match self {
+            Shell::Bash | Shell::Zsh => format!("{}:{}", value.replace('\\', "\\\\").replace(':', "\\:"), description),
+            Shell::Fish | Shell::Nu => format!("{}\t{}", value, description),
+        }
+    }
+}
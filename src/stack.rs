@@ -1,21 +1,24 @@
 #![allow(dead_code)]
 
-use std::{fs, usize};
+use std::fs;
 use std::fs::File;
 use std::io::{Error, Result};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use serde_json::Value;
 use sysinfo::{Pid, System};
 use dirs::home_dir;
 
 use crate::make_padding_string;
-use super::{apply_format, config::*, util::to_rooted};
+use super::{apply_format, config::*, records::Record, util::to_rooted};
 
 #[derive(Debug, Clone)]
 pub struct Stack {
     pid: u32,
     path: PathBuf,
     stack: Vec<PathBuf>,
+    /// keep only the newest occurence of a path (`dedup_stack`)
+    dedup: bool,
 }
 
 impl Stack {
@@ -26,6 +29,7 @@ impl Stack {
             pid: process_id,
             path: PathBuf::new(),
             stack: Vec::<PathBuf>::new(),
+            dedup: config.general.dedup_stack,
         };
         stack.build_stack(config)?;
 
@@ -59,15 +63,15 @@ impl Stack {
                 }
 
                 if item.is_dir() {
-                    let slash: String = apply_format(&"/".to_owned(), &config.styles.stack_punct_style)?;
+                    let slash: String = apply_format("/", &config.styles.stack_punct_style);
                     let mut segments: Vec<String> = path.split('/').map(|element| element.to_owned()).collect();
                     for element in segments.iter_mut() {
-                        *element = apply_format(&element, &config.styles.stack_path_style)?;
+                        *element = apply_format(element, &config.styles.stack_path_style);
                     }
                     path = segments.join(&slash);
 
-                    number = apply_format(&number, &config.styles.stack_number_style)?;
-                    separator = apply_format(&separator, &config.styles.stack_separator_style)?;
+                    number = apply_format(&number, &config.styles.stack_number_style);
+                    separator = apply_format(&separator, &config.styles.stack_separator_style);
                 }
 
                 let mut line: String;
@@ -79,7 +83,7 @@ impl Stack {
                     line = format!("{}{}{}{}\n", number, separator, padding, path);
                 }
                 if !item.is_dir() {
-                    line = apply_format(&line, &config.styles.stack_invalid_style)?;
+                    line = apply_format(&line, &config.styles.stack_invalid_style);
                 }
 
                 buffer.push_str(&line);
@@ -88,6 +92,21 @@ impl Stack {
         Ok(buffer)
     }
 
+    /// returns the stack entries as records with the fields `index`, `path`
+    /// and `valid`, newest first like `to_formatted_string`
+    pub fn to_records(&self) -> Vec<Record> {
+        self.get_entries().iter().enumerate().map(|(n, path)| vec![
+            ("index", Value::from(n)),
+            ("path", Value::from(path.to_string_lossy().to_string())),
+            ("valid", Value::from(path.is_dir())),
+        ]).collect()
+    }
+
+    /// returns the stack entries, newest first (index equals entry number)
+    pub fn get_entries(&self) -> Vec<&PathBuf> {
+        self.stack.iter().rev().collect()
+    }
+
     /// clear stack by deleting the associated stack file
     pub fn clear_stack(&mut self) -> Result<()> {
         fs::remove_file(self.path.clone())?;
@@ -101,6 +120,9 @@ impl Stack {
         to_rooted(&mut path)?;
 
         // append path to stack and write stack file to save changes
+        if self.dedup {
+            self.stack.retain(|entry| *entry != path);
+        }
         self.stack.push(path);
         self.write_stack_file()?;
         Ok(&self.stack)
@@ -134,7 +156,7 @@ impl Stack {
         }
         match self.stack.iter().rev().nth(entry_number) {
             Some(value) => Ok(value),
-            None => return Err(Error::other("-- failed to retrieve stack element #{entry_number}")),
+            None => Err(Error::other("-- failed to retrieve stack element #{entry_number}")),
         }
     }
 
@@ -149,7 +171,7 @@ impl Stack {
 
         let mut rotated_stack: Vec<PathBuf> = self.stack.drain(self.stack.len() - entry_number..).collect();
 
-        rotated_stack.extend(self.stack.drain(..));
+        rotated_stack.append(&mut self.stack);
         self.stack = rotated_stack;
 
         Ok(())
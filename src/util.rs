@@ -1,6 +1,6 @@
 use std::{
-    io::Result,
-    path::{Component, PathBuf},
+    io::{stderr, stdin, Error, IsTerminal, Result, Write},
+    path::{Component, Path, PathBuf},
 };
 
 /// returns absolute path but keeps links
@@ -9,14 +9,87 @@ pub fn to_rooted(path: &mut PathBuf) -> Result<()> {
         std::path::PathBuf::new()
     } else {
         std::env::current_dir()?
-    };    for component in path.components() {
+    };
+    for component in path.components() {
         match component {
             Component::CurDir => {},
             Component::ParentDir => { absolute.pop(); },
-            component @ _ => absolute.push(component.as_os_str()),
+            component => absolute.push(component.as_os_str()),
         }
     }
     *path = absolute;
     Ok(())
 }
 
+
+/// replaces a leading `~` with the home directory, other paths are returned as they are
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// checks whether `pattern` contains wildcard characters
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// matches `name` against a shell style glob pattern
+/// supported wildcards are `*` (any sequence) and `?` (any single character)
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // position of the last `*` in the pattern and the position in name it matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&character| character == '*')
+}
+
+/// asks the user for confirmation on stderr and reads the answer from stdin
+/// (stdout is evaluated by the calling shell and can't be used for prompts)
+pub fn confirm(prompt: &str) -> Result<bool> {
+    if !stdin().is_terminal() {
+        return Err(Error::other("-- cannot ask for confirmation, stdin is not a terminal (use `--yes`)"));
+    }
+    eprint!("{} [y/N] ", prompt);
+    stderr().flush()?;
+    let mut answer = String::new();
+    stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// formats seconds since the unix epoch as `YYYY-MM-DD hh:mm:ss` (UTC)
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    // convert days since epoch to a civil date (proleptic gregorian calendar)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
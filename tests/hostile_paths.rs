@@ -0,0 +1,63 @@
+//! evaluates `navigate init bash` in a clean bash and checks that directories
+//! and bookmarks with hostile names are quoted instead of evaluated
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// runs `script` in `bash --norc` with `navigate` on the path, the
+/// configuration directory pointing into `root` and `variables` set
+fn run_bash(root: &Path, script: &str, variables: &[(&str, &Path)]) -> String {
+    let binary = PathBuf::from(env!("CARGO_BIN_EXE_navigate"));
+    let mut path = binary.parent().unwrap().as_os_str().to_owned();
+    path.push(":");
+    path.push(env::var_os("PATH").unwrap_or_default());
+
+    let output = Command::new("bash")
+        .args(["--norc", "--noprofile", "-c"])
+        .arg(format!("eval \"$(navigate init bash)\" || exit 1\n{}", script))
+        .env("PATH", path)
+        .env("XDG_CONFIG_HOME", root.join("config"))
+        .envs(variables.iter().copied())
+        .current_dir(root)
+        .output()
+        .expect("failed to run bash");
+    assert!(output.status.success(), "bash failed: {}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn hostile_names_are_not_evaluated() {
+    let root = env::temp_dir().join(format!("navigate-hostile-test-{}", std::process::id()));
+    let start = root.join("start");
+    let hostile = root.join("it's $(touch pwned) `touch pwned` \\ '; touch pwned; '");
+    fs::create_dir_all(&start).unwrap();
+    fs::create_dir_all(&hostile).unwrap();
+    fs::create_dir_all(root.join("config")).unwrap();
+    let (start, hostile) = (start.canonicalize().unwrap(), hostile.canonicalize().unwrap());
+
+    // the paths are handed to bash in variables, so the script itself needs no quoting
+    let stdout = run_bash(&root, "
+cd \"$START\"
+push \"$HOSTILE\"
+printf '%s\\0' \"$PWD\"
+pop
+printf '%s\\0' \"$PWD\"
+book add \"o'neil \\$(touch pwned)\" \"$HOSTILE\" >/dev/null
+book \"o'neil \\$(touch pwned)\"
+printf '%s\\0' \"$PWD\"
+book list >/dev/null
+push =0
+printf '%s\\0' \"$PWD\"
+", &[("START", &start), ("HOSTILE", &hostile)]);
+
+    let lines: Vec<&str> = stdout.split_terminator('\0').collect();
+    let (start_str, hostile_str) = (start.to_str().unwrap(), hostile.to_str().unwrap());
+    assert_eq!(lines, vec![hostile_str, start_str, hostile_str, start_str]);
+    for directory in [&root, &start, &hostile] {
+        assert!(!directory.join("pwned").exists(), "command injected in {}", directory.display());
+    }
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,99 @@
+//! evaluates `navigate init zsh` in a clean zsh (`zsh -f`) and checks that
+//! the commands emitted by `navigate` change the directory of the shell
+//! the test needs zsh and is ignored by default, run it with `cargo test -- --ignored`,
+//! the script itself is checked for assignments to read-only parameters of zsh
+//! and run once if zsh is present
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn zsh_available() -> bool {
+    Command::new("zsh")
+        .args(["-f", "-c", "true"])
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// runs `script` in `zsh -f` with `navigate` on the path and the
+/// configuration directory pointing into `root`
+fn run_zsh(root: &Path, script: &str) -> String {
+    let binary = PathBuf::from(env!("CARGO_BIN_EXE_navigate"));
+    let mut path = binary.parent().unwrap().as_os_str().to_owned();
+    path.push(":");
+    path.push(env::var_os("PATH").unwrap_or_default());
+
+    let output = Command::new("zsh")
+        .arg("-f")
+        .arg("-c")
+        .arg(format!("eval \"$(navigate init zsh)\" || exit 1\n{}", script))
+        .env("PATH", path)
+        .env("XDG_CONFIG_HOME", root.join("config"))
+        .output()
+        .expect("failed to run zsh");
+    assert!(output.status.success(), "zsh failed: {}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+#[ignore = "needs zsh, run with `cargo test -- --ignored`"]
+fn zsh_functions_change_directory() {
+    assert!(zsh_available(), "zsh not found, the zsh integration test needs zsh");
+    let root = env::temp_dir().join(format!("navigate-zsh-test-{}", std::process::id()));
+    let first = root.join("first");
+    let second = root.join("second dir");
+    fs::create_dir_all(&first).unwrap();
+    fs::create_dir_all(&second).unwrap();
+    fs::create_dir_all(root.join("config")).unwrap();
+    let (first, second) = (first.canonicalize().unwrap(), second.canonicalize().unwrap());
+
+    let stdout = run_zsh(&root, &format!("
+cd '{first}'
+push '{second}'
+print -r -- \"$PWD\"
+pop
+print -r -- \"$PWD\"
+book add test '{second}' >/dev/null
+book test
+print -r -- \"$PWD\"
+push =0
+print -r -- \"$PWD\"
+", first = first.display(), second = second.display()));
+
+    let lines: Vec<&str> = stdout.lines().collect();
+    let (first, second) = (first.to_str().unwrap(), second.to_str().unwrap());
+    assert_eq!(lines, vec![second, first, second, first]);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+/// special parameters of zsh which cannot be assigned, e.g. `local status`
+const READ_ONLY: [&str; 3] = ["status", "ARGC", "PPID"];
+
+#[test]
+fn zsh_script_assigns_no_read_only_parameters() {
+    let output = Command::new(env!("CARGO_BIN_EXE_navigate"))
+        .args(["init", "zsh"])
+        .output()
+        .expect("failed to run navigate");
+    let script = String::from_utf8_lossy(&output.stdout);
+    for line in script.lines().map(str::trim) {
+        let assigned: Vec<&str> = match line.split_once(' ') {
+            Some(("local" | "typeset" | "declare", names)) => names.split_whitespace()
+                .map(|name| name.split('=').next().unwrap_or_default())
+                .collect(),
+            _ => line.split_once('=').map(|(name, _)| name).into_iter().collect(),
+        };
+        for name in assigned {
+            assert!(!READ_ONLY.contains(&name), "`{}` is read-only in zsh: {}", name, line);
+        }
+    }
+
+    if zsh_available() {
+        let root = env::temp_dir().join(format!("navigate-zsh-status-{}", std::process::id()));
+        fs::create_dir_all(root.join("config")).unwrap();
+        run_zsh(&root, "stack >/dev/null");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}